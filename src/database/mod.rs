@@ -1 +1 @@
-pub mod postgres;
\ No newline at end of file
+pub mod postgres;
@@ -1,4 +1,4 @@
-use crate::res::Res;
+use crate::{log::Log, res::Res};
 use axum::async_trait;
 use axum::extract::{FromRequest, FromRequestParts};
 use axum::http::request::Parts;
@@ -6,6 +6,7 @@ use axum::http::Request;
 use bb8::{Pool, PooledConnection};
 use diesel_async::pooled_connection::AsyncDieselConnectionManager;
 use diesel_async::AsyncPgConnection;
+use std::fmt::Display;
 
 pub type PgPool = AsyncDieselConnectionManager<AsyncPgConnection>;
 
@@ -36,7 +37,7 @@ where
             .expect("未设置 PgPool")
             .clone();
 
-        let conn = pool.get_owned().await.map_err(Res::internal_error)?;
+        let conn = pool.get_owned().await.map_err(db_error)?;
 
         Ok(Self(conn))
     }
@@ -56,8 +57,14 @@ where
             .expect("未设置 PgPool")
             .clone();
 
-        let conn = pool.get_owned().await.map_err(Res::internal_error)?;
+        let conn = pool.get_owned().await.map_err(db_error)?;
 
         Ok(Self(conn))
     }
 }
+
+/// 记录数据库错误 响应中只返回通用提示 避免泄露连接池与 sql 的错误信息
+pub(crate) fn db_error<E: Display>(err: E) -> Res<()> {
+    Log::error(format!("数据库错误 {err}"));
+    Res::internal_error("服务器内部错误")
+}
@@ -4,7 +4,7 @@ use axum::{
     routing::{get, post},
     Extension, Router,
 };
-use mll_axum_utils::middleware::interceptor;
+use mll_axum_utils::middleware::ip::IpAcl;
 use mll_axum_utils::{
     log::Log,
     middleware::{
@@ -29,8 +29,8 @@ async fn main() {
         .route("/login", post(login))
         // jwt 验证
         .layer(JwtAuth::<Claims>::new(vec!["/login"]))
-        // 拦截器拦截黑名单 ip 访问
-        .layer(interceptor::blacklist_ip(vec!["127.0.0.1"]))
+        // 拒绝黑名单 ip 访问
+        .layer(IpAcl::new().deny("127.0.0.1"))
         // 访问日志记录
         .layer(Logger::default());
 
@@ -44,7 +44,7 @@ async fn login(VJsonOrForm(user): VJsonOrForm<User>) -> utils::Result<String> {
     Log::debug(&user);
     let token = Claims::new(user).encode()?;
     // some validation
-    Ok(Res::success("登录成功", token))
+    Ok(Res::ok(token))
 }
 
 async fn index(Extension(token): Extension<Claims>) -> &'static str {
@@ -0,0 +1,244 @@
+use std::{
+    borrow::Cow,
+    marker::PhantomData,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    http::Request,
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use serde::de::DeserializeOwned;
+use tower::{Layer, Service};
+
+use super::jwt::Authority;
+use crate::{database::postgres::db_error, res::Res, utils::timestamp};
+
+mod store;
+
+pub use store::*;
+
+/// 通过验证的 api key 由 `ApiKeyAuth` 放入请求扩展
+///
+/// 实现了 `Authority` 可配合 `Authorize::<ApiKey<P>>::any_scope` 按权限限制路由
+#[derive(Debug, Clone)]
+pub struct ApiKey<P> {
+    /// key id
+    pub id: String,
+    /// 调用方信息
+    pub principal: P,
+    /// 权限
+    pub scopes: Vec<String>,
+}
+
+impl<P> Authority for ApiKey<P> {
+    fn scopes(&self) -> Vec<&str> {
+        self.scopes.iter().map(String::as_str).collect()
+    }
+}
+
+/// api key 验证中间件
+///
+/// 从请求头读取 key 按哈希在存储中查找 验证通过后将 `ApiKey<P>` 放入请求扩展
+///
+/// # Examples
+/// ```ignore
+/// #[derive(Debug, Clone, Serialize, Deserialize)]
+/// struct Partner {
+///     name: String,
+/// }
+///
+/// let store = MemoryApiKeyStore::new();
+/// let (key, record) = ApiKeyRecord::generate(&Partner { name: "cron".into() })?;
+/// store.insert(&record.scopes(["report:read"])).await?;
+///
+/// let app = Router::new()
+///     .route("/reports", get(reports))
+///     .route_layer(Authorize::<ApiKey<Partner>>::any_scope(["report:read"]))
+///     .layer(ApiKeyAuth::<Partner>::new(store).header("X-Api-Key"));
+///
+/// async fn reports(Extension(key): Extension<ApiKey<Partner>>) -> String {
+///     key.principal.name
+/// }
+/// ```
+pub struct ApiKeyAuth<P> {
+    store: Arc<dyn ApiKeyStore>,
+    header: Cow<'static, str>,
+    _principal: PhantomData<fn() -> P>,
+}
+
+impl<P> Clone for ApiKeyAuth<P> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            header: self.header.clone(),
+            _principal: PhantomData,
+        }
+    }
+}
+
+impl<P> ApiKeyAuth<P> {
+    pub fn new<R: ApiKeyStore>(store: R) -> Self {
+        Self {
+            store: Arc::new(store),
+            header: Cow::Borrowed("X-Api-Key"),
+            _principal: PhantomData,
+        }
+    }
+
+    /// 读取 key 的请求头 默认 `X-Api-Key`
+    pub fn header<H: Into<Cow<'static, str>>>(mut self, header: H) -> Self {
+        self.header = header.into();
+        self
+    }
+}
+
+impl<S, P> Layer<S> for ApiKeyAuth<P> {
+    type Service = ApiKeyAuthService<S, P>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ApiKeyAuthService {
+            inner,
+            store: self.store.clone(),
+            header: self.header.clone(),
+            _principal: PhantomData,
+        }
+    }
+}
+
+pub struct ApiKeyAuthService<S, P> {
+    inner: S,
+    store: Arc<dyn ApiKeyStore>,
+    header: Cow<'static, str>,
+    _principal: PhantomData<fn() -> P>,
+}
+
+impl<S: Clone, P> Clone for ApiKeyAuthService<S, P> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            store: self.store.clone(),
+            header: self.header.clone(),
+            _principal: PhantomData,
+        }
+    }
+}
+
+impl<S, P> Service<Request<Body>> for ApiKeyAuthService<S, P>
+where
+    P: DeserializeOwned + Clone + Send + Sync + 'static,
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let key = req
+            .headers()
+            .get(self.header.as_ref())
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+        let Some(key) = key else {
+            return Box::pin(async {
+                Ok(Res::<()>::auth("请求未携带 api key").into_response())
+            });
+        };
+
+        let store = self.store.clone();
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            match verify::<P>(&store, &key).await {
+                Ok(api_key) => {
+                    req.extensions_mut().insert(api_key);
+                    inner.call(req).await
+                }
+                Err(err_res) => Ok(err_res.into_response()),
+            }
+        })
+    }
+}
+
+async fn verify<P: DeserializeOwned>(
+    store: &Arc<dyn ApiKeyStore>,
+    key: &str,
+) -> Result<ApiKey<P>, Res<()>> {
+    let record = store
+        .find(&hash_key(key))
+        .await?
+        .ok_or_else(|| Res::auth("无效的 api key"))?;
+
+    if record.expires_at.is_some_and(|exp| exp <= timestamp()) {
+        return Err(Res::auth("api key 已过期"));
+    }
+
+    let principal = serde_json::from_value(record.principal).map_err(db_error)?;
+    Ok(ApiKey {
+        id: record.id,
+        principal,
+        scopes: record.scopes,
+    })
+}
+
+#[test]
+fn api_key_verify() {
+    use axum::{http::StatusCode, routing::get, Extension, Router};
+    use tower::ServiceExt;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let store = MemoryApiKeyStore::new();
+        let (key, record) = ApiKeyRecord::generate(&"cron").unwrap();
+        store
+            .insert(&record.clone().scopes(["report:read"]))
+            .await
+            .unwrap();
+        let (expired, expired_record) = ApiKeyRecord::generate(&"old").unwrap();
+        store.insert(&expired_record.expires_at(1)).await.unwrap();
+        let (revoked, revoked_record) = ApiKeyRecord::generate(&"gone").unwrap();
+        store.insert(&revoked_record).await.unwrap();
+        store.revoke(&revoked_record.id).await.unwrap();
+
+        let handler = |Extension(key): Extension<ApiKey<String>>| async move {
+            format!("{}:{}", key.principal, key.scopes.join(","))
+        };
+        let app = Router::new()
+            .route("/", get(handler))
+            .layer(ApiKeyAuth::<String>::new(store).header("Authorization-Key"));
+        let call = |header: &str, key: &str| {
+            let req = Request::get("/").header(header, key).body(Body::empty());
+            app.clone().oneshot(req.unwrap())
+        };
+
+        let res = call("Authorization-Key", &format!(" {key} "))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let mut body = res.into_body();
+        let body = axum::body::HttpBody::data(&mut body)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(body, "cron:report:read");
+
+        // 只读取配置的请求头
+        let res = call("X-Api-Key", &key).await.unwrap();
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        for key in [&expired, &revoked, &format!("{key}x")] {
+            let res = call("Authorization-Key", key).await.unwrap();
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+        }
+    });
+}
@@ -0,0 +1,217 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use axum::async_trait;
+use bb8::Pool;
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Nullable, Text},
+    QueryableByName,
+};
+use diesel_async::RunQueryDsl;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::{
+    database::postgres::{db_error, PgPool},
+    res::Res,
+};
+
+/// api key 记录 只保存 key 的哈希
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    /// key id 用于吊销
+    pub id: String,
+    /// key 的 sha256 哈希
+    pub hash: String,
+    /// 调用方信息
+    pub principal: Value,
+    /// 权限
+    pub scopes: Vec<String>,
+    /// 过期时间 None 为永不过期
+    pub expires_at: Option<u64>,
+}
+
+impl ApiKeyRecord {
+    /// 生成新的 key 返回明文 key 与记录 明文 key 只在此时可见
+    /// # Examples
+    /// ```
+    /// use mll_axum_utils::middleware::api_key::ApiKeyRecord;
+    /// let (key, record) = ApiKeyRecord::generate(&"cron").unwrap();
+    /// let record = record.scopes(["report:read"]).expires_at(1893456000);
+    /// ```
+    pub fn generate<P: Serialize>(principal: &P) -> Result<(String, Self), Res<()>> {
+        let key = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+        let record = Self {
+            id: Uuid::new_v4().to_string(),
+            hash: hash_key(&key),
+            principal: serde_json::to_value(principal).map_err(Res::internal_error)?,
+            scopes: vec![],
+            expires_at: None,
+        };
+        Ok((key, record))
+    }
+
+    pub fn scopes<I: IntoIterator<Item = S>, S: Into<String>>(mut self, scopes: I) -> Self {
+        self.scopes = scopes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn expires_at(mut self, expires_at: u64) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+}
+
+/// 计算 key 的 sha256 哈希
+pub fn hash_key(key: &str) -> String {
+    Sha256::digest(key.as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// api key 存储
+#[async_trait]
+pub trait ApiKeyStore: Send + Sync + 'static {
+    /// 按哈希查找 key
+    async fn find(&self, hash: &str) -> Result<Option<ApiKeyRecord>, Res<()>>;
+
+    /// 保存 key
+    async fn insert(&self, record: &ApiKeyRecord) -> Result<(), Res<()>>;
+
+    /// 吊销 key
+    async fn revoke(&self, id: &str) -> Result<(), Res<()>>;
+}
+
+/// 内存 api key 存储
+#[derive(Default)]
+pub struct MemoryApiKeyStore {
+    keys: Mutex<HashMap<String, ApiKeyRecord>>,
+}
+
+impl MemoryApiKeyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ApiKeyStore for MemoryApiKeyStore {
+    async fn find(&self, hash: &str) -> Result<Option<ApiKeyRecord>, Res<()>> {
+        let keys = self.keys.lock().unwrap_or_else(|err| err.into_inner());
+        Ok(keys.get(hash).cloned())
+    }
+
+    async fn insert(&self, record: &ApiKeyRecord) -> Result<(), Res<()>> {
+        let mut keys = self.keys.lock().unwrap_or_else(|err| err.into_inner());
+        keys.insert(record.hash.clone(), record.clone());
+        Ok(())
+    }
+
+    async fn revoke(&self, id: &str) -> Result<(), Res<()>> {
+        let mut keys = self.keys.lock().unwrap_or_else(|err| err.into_inner());
+        keys.retain(|_, record| record.id != id);
+        Ok(())
+    }
+}
+
+/// Postgres api key 存储
+///
+/// 使用前需调用 `migrate` 创建 `api_keys` 表
+pub struct PgApiKeyStore {
+    pool: Pool<PgPool>,
+}
+
+#[derive(QueryableByName)]
+struct Row {
+    #[diesel(sql_type = Text)]
+    id: String,
+    #[diesel(sql_type = Text)]
+    hash: String,
+    #[diesel(sql_type = Text)]
+    principal: String,
+    #[diesel(sql_type = Text)]
+    scopes: String,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    expires_at: Option<i64>,
+}
+
+impl PgApiKeyStore {
+    pub fn new(pool: Pool<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// 创建 `api_keys` 表
+    pub async fn migrate(&self) -> Result<(), Res<()>> {
+        let mut conn = self.pool.get().await.map_err(db_error)?;
+        sql_query(
+            "CREATE TABLE IF NOT EXISTS api_keys (
+                id TEXT PRIMARY KEY,
+                hash TEXT NOT NULL UNIQUE,
+                principal TEXT NOT NULL,
+                scopes TEXT NOT NULL,
+                expires_at BIGINT
+            )",
+        )
+        .execute(&mut conn)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ApiKeyStore for PgApiKeyStore {
+    async fn find(&self, hash: &str) -> Result<Option<ApiKeyRecord>, Res<()>> {
+        let mut conn = self.pool.get().await.map_err(db_error)?;
+        let rows = sql_query(
+            "SELECT id, hash, principal, scopes, expires_at FROM api_keys WHERE hash = $1",
+        )
+        .bind::<Text, _>(hash)
+        .load::<Row>(&mut conn)
+        .await
+        .map_err(db_error)?;
+
+        let Some(row) = rows.into_iter().next() else {
+            return Ok(None);
+        };
+        Ok(Some(ApiKeyRecord {
+            id: row.id,
+            hash: row.hash,
+            principal: serde_json::from_str(&row.principal).map_err(db_error)?,
+            scopes: serde_json::from_str(&row.scopes).map_err(db_error)?,
+            expires_at: row.expires_at.map(|exp| exp as u64),
+        }))
+    }
+
+    async fn insert(&self, record: &ApiKeyRecord) -> Result<(), Res<()>> {
+        let mut conn = self.pool.get().await.map_err(db_error)?;
+        let principal = serde_json::to_string(&record.principal).map_err(db_error)?;
+        let scopes = serde_json::to_string(&record.scopes).map_err(db_error)?;
+        sql_query(
+            "INSERT INTO api_keys (id, hash, principal, scopes, expires_at)
+            VALUES ($1, $2, $3, $4, $5)",
+        )
+        .bind::<Text, _>(&record.id)
+        .bind::<Text, _>(&record.hash)
+        .bind::<Text, _>(principal)
+        .bind::<Text, _>(scopes)
+        .bind::<Nullable<BigInt>, _>(record.expires_at.map(|exp| exp as i64))
+        .execute(&mut conn)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn revoke(&self, id: &str) -> Result<(), Res<()>> {
+        let mut conn = self.pool.get().await.map_err(db_error)?;
+        sql_query("DELETE FROM api_keys WHERE id = $1")
+            .bind::<Text, _>(id)
+            .execute(&mut conn)
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    future::Future,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, SaltString},
+    Argon2, PasswordHasher, PasswordVerifier,
+};
+use axum::{
+    async_trait,
+    body::Body,
+    headers::{authorization::Basic, Authorization, HeaderMapExt},
+    http::{header::WWW_AUTHENTICATE, HeaderValue, Request},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use once_cell::sync::Lazy;
+use tower::{Layer, Service};
+
+use super::filter::{FilterMode, PathFilter};
+use crate::res::Res;
+
+/// 通过验证的用户名 由 `BasicAuth` 放入请求扩展
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BasicUser(pub String);
+
+/// 用户名密码验证器
+#[async_trait]
+pub trait BasicVerifier: Send + Sync + 'static {
+    /// 用户名密码是否正确
+    async fn verify(&self, username: &str, password: &str) -> Result<bool, Res<()>>;
+}
+
+/// 异步闭包验证器
+/// # Examples
+/// ```ignore
+/// BasicAuth::protect(filter).verifier(|username: String, password: String| async move {
+///     Ok(username == "admin" && password == env::var("ADMIN_PASSWORD").unwrap_or_default())
+/// });
+/// ```
+#[async_trait]
+impl<F, Fut> BasicVerifier for F
+where
+    F: Fn(String, String) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<bool, Res<()>>> + Send,
+{
+    async fn verify(&self, username: &str, password: &str) -> Result<bool, Res<()>> {
+        self(username.to_string(), password.to_string()).await
+    }
+}
+
+/// 用户列表 只保存密码的 argon2 哈希 (PHC 格式)
+/// # Examples
+/// ```
+/// use mll_axum_utils::middleware::basic_auth::BasicUsers;
+/// let hash = BasicUsers::hash("correct horse");
+/// assert!(hash.starts_with("$argon2id$"));
+/// let users = BasicUsers::new().user("admin", hash);
+/// assert!(users.check("admin", "correct horse"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BasicUsers {
+    users: Arc<HashMap<String, String>>,
+}
+
+/// 用户不存在时用于校验的哈希 使耗时与用户存在时一致
+static DUMMY_HASH: Lazy<String> = Lazy::new(|| BasicUsers::hash("dummy"));
+
+impl BasicUsers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加用户 `hash` 为 `BasicUsers::hash` 生成的 PHC 格式哈希
+    pub fn user<U: Into<String>, H: Into<String>>(mut self, username: U, hash: H) -> Self {
+        let hash = hash.into();
+        assert!(PasswordHash::new(&hash).is_ok(), "密码哈希需为 PHC 格式");
+        Arc::make_mut(&mut self.users).insert(username.into(), hash);
+        self
+    }
+
+    /// 使用随机盐计算密码的 argon2id 哈希
+    pub fn hash(password: &str) -> String {
+        let salt = SaltString::generate(&mut OsRng);
+        Argon2::default()
+            .hash_password(password.as_bytes(), &salt)
+            .expect("argon2 默认参数可哈希任意密码")
+            .to_string()
+    }
+
+    /// 用户名密码是否正确
+    pub fn check(&self, username: &str, password: &str) -> bool {
+        let (hash, exists) = match self.users.get(username) {
+            Some(hash) => (hash.as_str(), true),
+            None => (DUMMY_HASH.as_str(), false),
+        };
+        let verified = PasswordHash::new(hash).is_ok_and(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        });
+        exists && verified
+    }
+}
+
+#[async_trait]
+impl BasicVerifier for BasicUsers {
+    async fn verify(&self, username: &str, password: &str) -> Result<bool, Res<()>> {
+        // argon2 较耗 CPU 放到阻塞线程池中执行 克隆只复制 Arc
+        let (users, username, password) =
+            (self.clone(), username.to_string(), password.to_string());
+        tokio::task::spawn_blocking(move || users.check(&username, &password))
+            .await
+            .map_err(|_| Res::internal_error("服务器内部错误"))
+    }
+}
+
+/// HTTP Basic 认证中间件 适用于内部管理页面 监控接口等
+///
+/// 验证通过后将 `BasicUser` 放入请求扩展 失败时返回带 `WWW-Authenticate` 的 401
+///
+/// # Examples
+/// ```ignore
+/// let users = BasicUsers::new().user("admin", env::var("ADMIN_PASSWORD_HASH")?);
+///
+/// let app = Router::new()
+///     .route("/metrics", get(metrics))
+///     .route("/admin/*path", get(admin))
+///     .layer(
+///         BasicAuth::protect(PathFilter::new().rule("/metrics").rule(Rule::prefix("/admin")))
+///             .users(users)
+///             .realm("admin"),
+///     );
+/// ```
+#[derive(Clone)]
+pub struct BasicAuth {
+    filter: Arc<PathFilter>,
+    mode: FilterMode,
+    verifier: Arc<dyn BasicVerifier>,
+    realm: Cow<'static, str>,
+}
+
+impl BasicAuth {
+    /// 匹配的路由跳过验证 其余路由需要验证
+    pub fn exempt(filter: PathFilter) -> Self {
+        Self::with_filter(filter, FilterMode::Exempt)
+    }
+
+    /// 仅匹配的路由需要验证 其余路由直接放行
+    pub fn protect(filter: PathFilter) -> Self {
+        Self::with_filter(filter, FilterMode::Protect)
+    }
+
+    fn with_filter(filter: PathFilter, mode: FilterMode) -> Self {
+        Self {
+            filter: Arc::new(filter),
+            mode,
+            verifier: Arc::new(BasicUsers::new()),
+            realm: Cow::Borrowed("Restricted"),
+        }
+    }
+
+    /// 使用用户列表验证
+    pub fn users(self, users: BasicUsers) -> Self {
+        self.verifier(users)
+    }
+
+    /// 使用自定义验证器 如查询数据库
+    pub fn verifier<V: BasicVerifier>(mut self, verifier: V) -> Self {
+        self.verifier = Arc::new(verifier);
+        self
+    }
+
+    /// 认证域 浏览器弹窗中显示 默认 `Restricted`
+    pub fn realm<R: Into<Cow<'static, str>>>(mut self, realm: R) -> Self {
+        self.realm = realm.into();
+        self
+    }
+}
+
+impl<S> Layer<S> for BasicAuth {
+    type Service = BasicAuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        BasicAuthService {
+            inner,
+            filter: self.filter.clone(),
+            mode: self.mode,
+            verifier: self.verifier.clone(),
+            realm: self.realm.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct BasicAuthService<S> {
+    inner: S,
+    filter: Arc<PathFilter>,
+    mode: FilterMode,
+    verifier: Arc<dyn BasicVerifier>,
+    realm: Cow<'static, str>,
+}
+
+impl<S> Service<Request<Body>> for BasicAuthService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        if !self.mode.applies(&self.filter, &req) {
+            return Box::pin(self.inner.call(req));
+        }
+
+        let realm = self.realm.clone();
+        let Some(credentials) = req.headers().typed_get::<Authorization<Basic>>() else {
+            return Box::pin(async move { Ok(challenge(&realm, "请求未携带认证信息")) });
+        };
+
+        let verifier = self.verifier.clone();
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let (username, password) = (credentials.username(), credentials.password());
+            match verifier.verify(username, password).await {
+                Ok(true) => {
+                    req.extensions_mut().insert(BasicUser(username.to_string()));
+                    inner.call(req).await
+                }
+                Ok(false) => Ok(challenge(&realm, "用户名或密码错误")),
+                Err(err_res) => Ok(err_res.into_response()),
+            }
+        })
+    }
+}
+
+/// 401 响应 携带 `WWW-Authenticate` 以便浏览器弹出登录框
+fn challenge(realm: &str, msg: &str) -> Response {
+    let mut res = Res::<()>::auth(msg).into_response();
+    let realm = realm.replace('"', "");
+    if let Ok(value) = HeaderValue::try_from(format!("Basic realm=\"{realm}\", charset=\"UTF-8\""))
+    {
+        res.headers_mut().insert(WWW_AUTHENTICATE, value);
+    }
+    res
+}
+
+#[test]
+fn basic_users_check() {
+    let hash = BasicUsers::hash("admin123");
+    assert_ne!(hash, BasicUsers::hash("admin123"));
+    let users = BasicUsers::new().user("admin", hash);
+    assert!(users.check("admin", "admin123"));
+    assert!(!users.check("admin", "admin"));
+    assert!(!users.check("root", "admin123"));
+}
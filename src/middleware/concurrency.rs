@@ -0,0 +1,352 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use axum::{
+    body::Body,
+    http::{header::RETRY_AFTER, HeaderValue, Request},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use serde::Serialize;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tower::{Layer, Service};
+
+use super::filter::{FilterMode, PathFilter};
+use crate::res::Res;
+
+/// 并发分组 限制匹配路由同时处理的请求数
+///
+/// 超出并发数的请求进入等待队列 队列已满或等待超时时返回 503
+#[derive(Clone)]
+pub struct ConcurrencyGroup {
+    limiter: Arc<Limiter>,
+    filter: Arc<PathFilter>,
+    mode: FilterMode,
+}
+
+struct Limiter {
+    name: String,
+    semaphore: Arc<Semaphore>,
+    max_in_flight: usize,
+    max_queued: usize,
+    timeout: Duration,
+    queued: AtomicUsize,
+}
+
+impl ConcurrencyGroup {
+    /// 默认队列长度与并发数相同 最长等待1s
+    pub fn new<N: Into<String>>(name: N, max_in_flight: usize) -> Self {
+        let limiter = Limiter {
+            name: name.into(),
+            semaphore: Arc::new(Semaphore::new(max_in_flight)),
+            max_in_flight,
+            max_queued: max_in_flight,
+            timeout: Duration::from_secs(1),
+            queued: AtomicUsize::new(0),
+        };
+        Self {
+            limiter: Arc::new(limiter),
+            filter: Arc::new(PathFilter::new()),
+            mode: FilterMode::Exempt,
+        }
+    }
+
+    /// 等待队列长度 为0时超出并发数直接返回 503
+    pub fn queue(mut self, max_queued: usize) -> Self {
+        self.limiter_mut().max_queued = max_queued;
+        self
+    }
+
+    /// 最长等待时间
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.limiter_mut().timeout = timeout;
+        self
+    }
+
+    /// 仅匹配的路由计入该分组
+    pub fn only(mut self, filter: PathFilter) -> Self {
+        self.filter = Arc::new(filter);
+        self.mode = FilterMode::Protect;
+        self
+    }
+
+    /// 匹配的路由不计入该分组
+    pub fn exempt(mut self, filter: PathFilter) -> Self {
+        self.filter = Arc::new(filter);
+        self.mode = FilterMode::Exempt;
+        self
+    }
+
+    fn limiter_mut(&mut self) -> &mut Limiter {
+        Arc::get_mut(&mut self.limiter).expect("ConcurrencyGroup 配置需在 layer 前完成")
+    }
+}
+
+impl Limiter {
+    /// 获取许可 队列已满或等待超时时返回 None
+    async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        if let Ok(permit) = self.semaphore.clone().try_acquire_owned() {
+            return Some(permit);
+        }
+        if self.queued.fetch_add(1, Ordering::SeqCst) >= self.max_queued {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+
+        // 请求被取消时同样需要离开队列
+        let _queued = Queued(&self.queued);
+        let permit = self.semaphore.clone().acquire_owned();
+        tokio::time::timeout(self.timeout, permit).await.ok()?.ok()
+    }
+
+    fn stats(&self) -> GroupStats {
+        GroupStats {
+            name: self.name.clone(),
+            in_flight: self.max_in_flight - self.semaphore.available_permits(),
+            queued: self.queued.load(Ordering::SeqCst),
+            max_in_flight: self.max_in_flight,
+            max_queued: self.max_queued,
+        }
+    }
+}
+
+/// 离开队列时减少计数
+struct Queued<'a>(&'a AtomicUsize);
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// 分组的并发与排队情况
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GroupStats {
+    pub name: String,
+    /// 处理中的请求数
+    pub in_flight: usize,
+    /// 排队中的请求数
+    pub queued: usize,
+    pub max_in_flight: usize,
+    pub max_queued: usize,
+}
+
+/// 并发限制与过载保护中间件
+///
+/// 请求按添加顺序获取所有匹配分组的许可 处理完成后释放
+/// 建议先添加范围小的分组 避免在分组队列中等待时占用全局许可
+///
+/// # Examples
+/// ```ignore
+/// let limit = ConcurrencyLimit::new()
+///     .group(
+///         ConcurrencyGroup::new("db", 16)
+///             .queue(32)
+///             .timeout(Duration::from_millis(500))
+///             .only(PathFilter::new().rule(Rule::prefix("/api/reports"))),
+///     )
+///     .group(ConcurrencyGroup::new("global", 512).exempt(PathFilter::from(vec!["/health"])))
+///     .retry_after(2);
+/// let handle = limit.handle();
+///
+/// let app = Router::new()
+///     .route("/api/reports", get(reports))
+///     .route("/health", get(move || async move { Res::ok(handle.stats()) }))
+///     .layer(limit);
+/// ```
+#[derive(Clone)]
+pub struct ConcurrencyLimit {
+    groups: Arc<Vec<ConcurrencyGroup>>,
+    retry_after: u64,
+}
+
+impl Default for ConcurrencyLimit {
+    fn default() -> Self {
+        Self {
+            groups: Arc::new(vec![]),
+            retry_after: 1,
+        }
+    }
+}
+
+impl ConcurrencyLimit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加分组
+    pub fn group(mut self, group: ConcurrencyGroup) -> Self {
+        Arc::make_mut(&mut self.groups).push(group);
+        self
+    }
+
+    /// 503 响应的 `Retry-After` 默认1s 单位 s
+    pub fn retry_after(mut self, retry_after: u64) -> Self {
+        self.retry_after = retry_after;
+        self
+    }
+
+    /// 查看各分组并发与排队情况的句柄 需在添加分组后获取
+    pub fn handle(&self) -> ConcurrencyHandle {
+        ConcurrencyHandle(self.groups.clone())
+    }
+}
+
+/// 并发情况 用于监控
+#[derive(Clone)]
+pub struct ConcurrencyHandle(Arc<Vec<ConcurrencyGroup>>);
+
+impl ConcurrencyHandle {
+    /// 各分组的并发与排队情况
+    pub fn stats(&self) -> Vec<GroupStats> {
+        self.0.iter().map(|group| group.limiter.stats()).collect()
+    }
+
+    /// 分组的排队请求数
+    pub fn queued(&self, name: &str) -> Option<usize> {
+        let group = self.0.iter().find(|group| group.limiter.name == name)?;
+        Some(group.limiter.queued.load(Ordering::SeqCst))
+    }
+}
+
+impl<S> Layer<S> for ConcurrencyLimit {
+    type Service = ConcurrencyLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConcurrencyLimitService {
+            inner,
+            groups: self.groups.clone(),
+            retry_after: self.retry_after,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ConcurrencyLimitService<S> {
+    inner: S,
+    groups: Arc<Vec<ConcurrencyGroup>>,
+    retry_after: u64,
+}
+
+impl<S> Service<Request<Body>> for ConcurrencyLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let limiters: Vec<_> = self
+            .groups
+            .iter()
+            .filter(|group| group.mode.applies(&group.filter, &req))
+            .map(|group| group.limiter.clone())
+            .collect();
+        if limiters.is_empty() {
+            return Box::pin(self.inner.call(req));
+        }
+
+        let retry_after = self.retry_after;
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let mut permits = Vec::with_capacity(limiters.len());
+            for limiter in &limiters {
+                match limiter.acquire().await {
+                    Some(permit) => permits.push(permit),
+                    None => return Ok(overloaded(retry_after)),
+                }
+            }
+
+            let res = inner.call(req).await;
+            drop(permits);
+            res
+        })
+    }
+}
+
+/// 503 响应
+fn overloaded(retry_after: u64) -> Response {
+    let mut res = Res::<()>::new(503u16, "服务繁忙 请稍后重试").into_response();
+    res.headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+    res
+}
+
+#[test]
+fn concurrency_queue() {
+    use axum::{http::StatusCode, routing::get, Router};
+    use tower::ServiceExt;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        // handler 在 gate 放行前一直处理中
+        let gate = Arc::new(Semaphore::new(0));
+        let handler_gate = gate.clone();
+        let limit = ConcurrencyLimit::new()
+            .group(
+                ConcurrencyGroup::new("api", 2)
+                    .queue(1)
+                    .timeout(Duration::from_millis(50)),
+            )
+            .retry_after(3);
+        let handle = limit.handle();
+        let app = Router::new()
+            .route(
+                "/",
+                get(move || {
+                    let gate = handler_gate.clone();
+                    async move {
+                        let _permit = gate.acquire().await.unwrap();
+                    }
+                }),
+            )
+            .layer(limit);
+        let call = || {
+            let app = app.clone();
+            tokio::spawn(async move {
+                let req = Request::get("/").body(Body::empty()).unwrap();
+                app.oneshot(req).await.unwrap()
+            })
+        };
+
+        let running = [call(), call()];
+        let waiting = call();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let stats = &handle.stats()[0];
+        assert_eq!((stats.in_flight, stats.queued), (2, 1));
+        assert_eq!(handle.queued("api"), Some(1));
+
+        // 队列已满时直接拒绝
+        let res = call().await.unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers()[RETRY_AFTER], "3");
+
+        // 等待超时后拒绝并离开队列
+        let res = waiting.await.unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(handle.queued("api"), Some(0));
+
+        gate.add_permits(2);
+        for task in running {
+            assert_eq!(task.await.unwrap().status(), StatusCode::OK);
+        }
+        let stats = &handle.stats()[0];
+        assert_eq!((stats.in_flight, stats.queued), (0, 0));
+    });
+}
@@ -0,0 +1,526 @@
+use std::{
+    borrow::Cow,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::{
+    async_trait,
+    body::Body,
+    extract::FromRequestParts,
+    headers::{Cookie, HeaderMapExt},
+    http::{
+        header::CONTENT_TYPE, header::SET_COOKIE, request::Parts, HeaderValue, Method, Request,
+        StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use bytes::Bytes;
+use futures_util::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use tower::{Layer, Service};
+use uuid::Uuid;
+
+use super::{filter::PathFilter, session::Session};
+use crate::res::Res;
+
+/// CSRF 校验失败原因 `code` 用于前端区分于普通 403
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsrfRejection {
+    /// 请求未携带 csrf token 或 cookie
+    Missing,
+    /// 提交的 token 与 cookie 不一致或签名无效
+    Mismatch,
+}
+
+#[derive(Serialize)]
+struct Reason {
+    code: u16,
+    reason: &'static str,
+}
+
+impl CsrfRejection {
+    /// 业务码
+    pub const fn code(&self) -> u16 {
+        match self {
+            CsrfRejection::Missing => 40301,
+            CsrfRejection::Mismatch => 40302,
+        }
+    }
+
+    /// 稳定的原因标识
+    pub const fn reason(&self) -> &'static str {
+        match self {
+            CsrfRejection::Missing => "csrf_token_missing",
+            CsrfRejection::Mismatch => "csrf_token_mismatch",
+        }
+    }
+}
+
+impl IntoResponse for CsrfRejection {
+    fn into_response(self) -> Response {
+        let reason = Reason {
+            code: self.code(),
+            reason: self.reason(),
+        };
+        Res::new_data(StatusCode::FORBIDDEN, "CSRF 校验失败", reason).into_response()
+    }
+}
+
+/// 读取请求的用户绑定值
+type Binding = fn(&Request<Body>) -> Option<String>;
+
+/// CSRF 防护中间件 使用签名的双重提交 cookie
+///
+/// 响应时写入 `csrf_token` cookie 非安全方法 (POST PUT PATCH DELETE) 需在请求头
+/// `X-CSRF-Token` 或表单字段 `csrf_token` 中提交相同的 token
+///
+/// multipart 表单不会读取请求体 需通过请求头提交
+///
+/// token 的签名包含用户绑定值 默认为 `SessionLayer` 的会话 id 需将 `SessionLayer` 放在外层
+/// 会话 id 变化 (如登录后重新生成 id) 时旧 token 失效 并在同一响应中签发新 token
+/// 可通过 `bind` 自定义绑定值
+///
+/// # Examples
+/// ```ignore
+/// let app = Router::new()
+///     .route("/profile", get(profile_page).post(update_profile))
+///     .route("/webhook", post(webhook))
+///     .layer(CsrfLayer::new(env::var("CSRF_SECRET")?).exempt(PathFilter::from(vec!["/webhook"])))
+///     .layer(SessionLayer::new(MemorySessionStore::new(), env::var("SESSION_SECRET")?));
+///
+/// async fn profile_page(csrf: CsrfToken) -> Html<String> {
+///     Html(format!(
+///         r#"<form method="post"><input type="hidden" name="csrf_token" value="{}"></form>"#,
+///         csrf.value()
+///     ))
+/// }
+/// ```
+#[derive(Clone)]
+pub struct CsrfLayer {
+    config: Arc<CsrfConfig>,
+}
+
+struct CsrfConfig {
+    secret: Vec<u8>,
+    cookie: Cow<'static, str>,
+    header: Cow<'static, str>,
+    field: Cow<'static, str>,
+    exempt: PathFilter,
+    path: Cow<'static, str>,
+    secure: bool,
+    /// 未设置时使用会话 id
+    binding: Option<Binding>,
+}
+
+impl CsrfLayer {
+    /// `secret` 用于签名 token
+    pub fn new<K: AsRef<[u8]>>(secret: K) -> Self {
+        let config = CsrfConfig {
+            secret: secret.as_ref().to_vec(),
+            cookie: Cow::Borrowed("csrf_token"),
+            header: Cow::Borrowed("X-CSRF-Token"),
+            field: Cow::Borrowed("csrf_token"),
+            exempt: PathFilter::new(),
+            path: Cow::Borrowed("/"),
+            secure: false,
+            binding: None,
+        };
+        Self {
+            config: Arc::new(config),
+        }
+    }
+
+    /// cookie 名称 默认 `csrf_token`
+    pub fn cookie_name<N: Into<Cow<'static, str>>>(mut self, name: N) -> Self {
+        self.config_mut().cookie = name.into();
+        self
+    }
+
+    /// 提交 token 的请求头 默认 `X-CSRF-Token`
+    pub fn header<H: Into<Cow<'static, str>>>(mut self, header: H) -> Self {
+        self.config_mut().header = header.into();
+        self
+    }
+
+    /// 提交 token 的表单字段 默认 `csrf_token`
+    pub fn field<F: Into<Cow<'static, str>>>(mut self, field: F) -> Self {
+        self.config_mut().field = field.into();
+        self
+    }
+
+    /// 跳过校验的路径 如第三方回调
+    pub fn exempt(mut self, filter: PathFilter) -> Self {
+        self.config_mut().exempt = filter;
+        self
+    }
+
+    /// cookie 的 Path 默认 `/`
+    pub fn path<P: Into<Cow<'static, str>>>(mut self, path: P) -> Self {
+        self.config_mut().path = path.into();
+        self
+    }
+
+    /// cookie 是否仅通过 https 发送
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.config_mut().secure = secure;
+        self
+    }
+
+    /// 自定义用户绑定值 如已登录用户的 id 返回 None 时 token 不绑定用户
+    ///
+    /// 绑定值只在请求时读取 handler 中的变化在下一次请求时签发新 token
+    /// # Examples
+    /// ```ignore
+    /// CsrfLayer::new(secret).bind(|req| req.extensions().get::<BasicUser>().map(|user| user.0.clone()));
+    /// ```
+    pub fn bind(mut self, binding: Binding) -> Self {
+        self.config_mut().binding = Some(binding);
+        self
+    }
+
+    fn config_mut(&mut self) -> &mut CsrfConfig {
+        Arc::get_mut(&mut self.config).expect("CsrfLayer 配置需在 layer 前完成")
+    }
+}
+
+impl<S> Layer<S> for CsrfLayer {
+    type Service = CsrfService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CsrfService {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CsrfService<S> {
+    inner: S,
+    config: Arc<CsrfConfig>,
+}
+
+impl<S> Service<Request<Body>> for CsrfService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let config = self.config.clone();
+        let session = req.extensions().get::<Session>().cloned();
+        let binding = match config.binding {
+            Some(binding) => binding(&req),
+            None => session.as_ref().and_then(Session::response_id),
+        };
+        let binding = binding.unwrap_or_default();
+        let cookie = config.cookie_token(&req, &binding);
+        let safe = matches!(
+            *req.method(),
+            Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+        );
+        let check = !safe && !config.exempt.matches(&req);
+
+        // cookie 中没有有效 token 时签发新 token
+        let (token, issued) = match &cookie {
+            Some(token) => (token.clone(), false),
+            None => (config.issue(&binding), true),
+        };
+        req.extensions_mut().insert(CsrfToken(token.clone()));
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            if check {
+                let (result, next) = config.check(&binding, cookie.as_deref(), req).await;
+                if let Err(rejection) = result {
+                    return Ok(rejection.into_response());
+                }
+                req = next;
+            }
+
+            let mut res = inner.call(req).await?;
+            // handler 中登录或退出导致会话 id 变化时 按新的会话签发 token
+            let rebound = match (config.binding, session) {
+                (None, Some(session)) => {
+                    let next = session.response_id().unwrap_or_default();
+                    (next != binding).then(|| config.issue(&next))
+                }
+                _ => None,
+            };
+            match rebound {
+                Some(token) => config.set_cookie(&mut res, &token),
+                None if issued => config.set_cookie(&mut res, &token),
+                None => {}
+            }
+            Ok(res)
+        })
+    }
+}
+
+impl CsrfConfig {
+    /// 读取 cookie 中签名有效且属于当前用户的 token
+    fn cookie_token<B>(&self, req: &Request<B>, binding: &str) -> Option<String> {
+        let cookie = req.headers().typed_get::<Cookie>()?;
+        let token = cookie.get(&self.cookie)?;
+        let (nonce, signature) = token.rsplit_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        self.mac(binding, nonce).verify_slice(&signature).ok()?;
+        Some(token.to_string())
+    }
+
+    /// 签发新的 token 格式为 `{nonce}.{签名}`
+    fn issue(&self, binding: &str) -> String {
+        let nonce = Uuid::new_v4().simple().to_string();
+        let signature = URL_SAFE_NO_PAD.encode(self.mac(binding, &nonce).finalize().into_bytes());
+        format!("{nonce}.{signature}")
+    }
+
+    /// 校验请求 校验需读取表单 因此返回请求供后续使用
+    async fn check(
+        &self,
+        binding: &str,
+        cookie: Option<&str>,
+        req: Request<Body>,
+    ) -> (Result<(), CsrfRejection>, Request<Body>) {
+        let Some(cookie) = cookie else {
+            return (Err(CsrfRejection::Missing), req);
+        };
+        match self.submitted(req).await {
+            (Some(submitted), req) => (self.verify(binding, cookie, &submitted), req),
+            (None, req) => (Err(CsrfRejection::Missing), req),
+        }
+    }
+
+    /// 提交的 token 是否与 cookie 一致
+    ///
+    /// 用 cookie 的签名验证提交的 nonce 比较耗时与内容无关
+    fn verify(&self, binding: &str, cookie: &str, submitted: &str) -> Result<(), CsrfRejection> {
+        let (nonce, _) = submitted.rsplit_once('.').ok_or(CsrfRejection::Mismatch)?;
+        let (_, signature) = cookie.rsplit_once('.').ok_or(CsrfRejection::Mismatch)?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| CsrfRejection::Mismatch)?;
+        self.mac(binding, nonce)
+            .verify_slice(&signature)
+            .map_err(|_| CsrfRejection::Mismatch)
+    }
+
+    /// 读取请求头或表单中提交的 token 读取表单后重新放回请求体
+    async fn submitted(&self, req: Request<Body>) -> (Option<String>, Request<Body>) {
+        let header = req
+            .headers()
+            .get(self.header.as_ref())
+            .and_then(|value| value.to_str().ok());
+        if let Some(token) = header {
+            return (Some(token.to_string()), req);
+        }
+
+        let is_form = req
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"));
+        if !is_form {
+            return (None, req);
+        }
+
+        let (parts, body) = req.into_parts();
+        let bytes = match read_body(body).await {
+            Some(bytes) => bytes,
+            None => return (None, Request::from_parts(parts, Body::empty())),
+        };
+        let token = form_urlencoded(&bytes, &self.field);
+        (token, Request::from_parts(parts, Body::from(bytes)))
+    }
+
+    /// 签名 `binding` 与 `nonce` 绑定值带长度前缀 避免拼接产生歧义
+    fn mac(&self, binding: &str, nonce: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC 可接受任意长度密钥");
+        mac.update(&(binding.len() as u64).to_be_bytes());
+        mac.update(binding.as_bytes());
+        mac.update(nonce.as_bytes());
+        mac
+    }
+
+    /// cookie 需要被前端脚本读取 不设置 HttpOnly
+    fn set_cookie(&self, res: &mut Response, token: &str) {
+        let secure = if self.secure { "; Secure" } else { "" };
+        let cookie = format!(
+            "{}={token}; Path={}; SameSite=Lax{secure}",
+            self.cookie, self.path
+        );
+        if let Ok(cookie) = HeaderValue::try_from(cookie) {
+            res.headers_mut().append(SET_COOKIE, cookie);
+        }
+    }
+}
+
+/// 读取完整请求体
+async fn read_body(body: Body) -> Option<Bytes> {
+    let req = Request::new(body);
+    axum::extract::FromRequest::from_request(req, &())
+        .await
+        .ok()
+}
+
+/// 读取表单中的字段
+fn form_urlencoded(bytes: &[u8], field: &str) -> Option<String> {
+    serde_urlencoded::from_bytes::<Vec<(String, String)>>(bytes)
+        .ok()?
+        .into_iter()
+        .find_map(|(key, value)| (key == field).then_some(value))
+}
+
+/// 当前请求的 csrf token 用于渲染表单隐藏字段或返回给前端 需配合 `CsrfLayer` 使用
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsrfToken(String);
+
+impl CsrfToken {
+    pub fn value(&self) -> &str {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CsrfToken
+where
+    S: Send + Sync,
+{
+    type Rejection = Res<()>;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<CsrfToken>()
+            .cloned()
+            .ok_or_else(|| Res::internal_error("未配置 CsrfLayer"))
+    }
+}
+
+/// 读取响应中写入的 cookie
+#[cfg(test)]
+fn response_cookie(res: &Response, name: &str) -> Option<String> {
+    res.headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find_map(|value| {
+            let (key, value) = value.split(';').next()?.split_once('=')?;
+            (key == name).then(|| value.to_string())
+        })
+}
+
+#[test]
+fn csrf_issue_and_verify() {
+    use axum::{routing::get, Router};
+    use tower::ServiceExt;
+
+    let app = Router::new()
+        .route("/", get(|| async {}).post(|body: String| async move { body }))
+        .layer(CsrfLayer::new("secret"));
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let res = app.clone().oneshot(Request::get("/").body(Body::empty()).unwrap());
+        let res = res.await.unwrap();
+        let token = response_cookie(&res, "csrf_token").unwrap();
+        let cookie = format!("csrf_token={token}");
+
+        // 已有有效 token 时不重复签发
+        let req = Request::get("/").header("cookie", &cookie);
+        let res = app.clone().oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        assert!(response_cookie(&res, "csrf_token").is_none());
+
+        let post = || Request::post("/").header("cookie", &cookie);
+        let res = app.clone().oneshot(post().body(Body::empty()).unwrap());
+        assert_eq!(res.await.unwrap().status(), StatusCode::FORBIDDEN);
+
+        let req = post().header("X-CSRF-Token", &token);
+        let res = app.clone().oneshot(req.body(Body::empty()).unwrap());
+        assert_eq!(res.await.unwrap().status(), StatusCode::OK);
+
+        let req = post().header("X-CSRF-Token", URL_SAFE_NO_PAD.encode("forged"));
+        let res = app.clone().oneshot(req.body(Body::empty()).unwrap());
+        assert_eq!(res.await.unwrap().status(), StatusCode::FORBIDDEN);
+
+        // 表单字段提交 读取后请求体仍传给 handler
+        let form = format!("name=a&csrf_token={token}");
+        let req = post().header(CONTENT_TYPE, "application/x-www-form-urlencoded");
+        let res = app.clone().oneshot(req.body(Body::from(form.clone())).unwrap());
+        let mut body = res.await.unwrap().into_body();
+        let body = axum::body::HttpBody::data(&mut body).await.unwrap().unwrap();
+        assert_eq!(body, form.as_bytes());
+    });
+}
+
+#[test]
+fn csrf_bound_to_session() {
+    use super::session::{MemorySessionStore, SessionLayer};
+    use axum::{routing::post, Router};
+    use tower::ServiceExt;
+
+    let app = Router::new()
+        .route("/", post(|| async {}))
+        .route(
+            "/login",
+            post(|session: Session| async move {
+                session.regenerate();
+                session.insert("user", 1).unwrap();
+            }),
+        )
+        .layer(CsrfLayer::new("secret"))
+        .layer(SessionLayer::new(MemorySessionStore::new(), "secret"));
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let request = |path: &str, sid: &str, token: &str| {
+            Request::post(path)
+                .header("cookie", format!("sid={sid}; csrf_token={token}"))
+                .header("X-CSRF-Token", token)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        // 两次登录得到两个会话 登录响应中按新会话签发 token
+        let login = |token: String| app.clone().oneshot(request("/login", "", &token));
+        let res = app.clone().oneshot(Request::get("/").body(Body::empty()).unwrap());
+        let anonymous = response_cookie(&res.await.unwrap(), "csrf_token").unwrap();
+        let res = login(anonymous.clone()).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let (sid_a, token_a) = (
+            response_cookie(&res, "sid").unwrap(),
+            response_cookie(&res, "csrf_token").unwrap(),
+        );
+        let res = login(anonymous.clone()).await.unwrap();
+        let (sid_b, token_b) = (
+            response_cookie(&res, "sid").unwrap(),
+            response_cookie(&res, "csrf_token").unwrap(),
+        );
+        assert_ne!(token_a, anonymous);
+
+        let res = app.clone().oneshot(request("/", &sid_a, &token_a));
+        assert_eq!(res.await.unwrap().status(), StatusCode::OK);
+        // 登录前的 token 与其他会话的 token 都不能使用
+        let res = app.clone().oneshot(request("/", &sid_a, &anonymous));
+        assert_eq!(res.await.unwrap().status(), StatusCode::FORBIDDEN);
+        let res = app.clone().oneshot(request("/", &sid_a, &token_b));
+        assert_eq!(res.await.unwrap().status(), StatusCode::FORBIDDEN);
+        let res = app.clone().oneshot(request("/", &sid_b, &token_b));
+        assert_eq!(res.await.unwrap().status(), StatusCode::OK);
+    });
+}
@@ -0,0 +1,276 @@
+use axum::{
+    extract::MatchedPath,
+    http::{Method, Request},
+};
+
+/// 路径匹配方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Pattern {
+    /// 完全匹配 忽略末尾的 `/`
+    Exact(String),
+    /// 前缀匹配 按路径段匹配 `/public` 匹配 `/public` 与 `/public/a` 不匹配 `/publicity`
+    Prefix(String),
+    /// 通配符匹配 `*` 匹配单个路径段内的任意字符 `**` 匹配任意多个路径段
+    Glob(String),
+    /// axum 路由模板 如 `/share/:id` 优先与 `MatchedPath` 比较
+    Route(String),
+}
+
+/// 路径规则 可限定请求方式
+///
+/// # Examples
+/// ```
+/// use axum::http::Method;
+/// use mll_axum_utils::middleware::filter::Rule;
+/// Rule::prefix("/public");
+/// Rule::glob("/static/**/*.png");
+/// Rule::route("/share/:id").method(Method::GET);
+/// // 字符串简写: 含 `:name` `*name` 为路由模板 含 `*` 为通配符 其余为完全匹配 可加请求方式前缀
+/// Rule::from("GET /articles/*");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pattern: Pattern,
+    methods: Vec<Method>,
+}
+
+impl Rule {
+    pub fn new(pattern: Pattern) -> Self {
+        Self {
+            pattern,
+            methods: vec![],
+        }
+    }
+
+    pub fn exact<P: Into<String>>(path: P) -> Self {
+        Self::new(Pattern::Exact(path.into()))
+    }
+
+    pub fn prefix<P: Into<String>>(path: P) -> Self {
+        Self::new(Pattern::Prefix(path.into()))
+    }
+
+    pub fn glob<P: Into<String>>(path: P) -> Self {
+        Self::new(Pattern::Glob(path.into()))
+    }
+
+    pub fn route<P: Into<String>>(path: P) -> Self {
+        Self::new(Pattern::Route(path.into()))
+    }
+
+    /// 限定请求方式 可多次调用 未限定时匹配所有请求方式
+    pub fn method(mut self, method: Method) -> Self {
+        self.methods.push(method);
+        self
+    }
+
+    /// 是否匹配 `matched` 为 axum 匹配到的路由模板
+    pub fn matches(&self, method: &Method, path: &str, matched: Option<&str>) -> bool {
+        if !self.methods.is_empty() && !self.methods.contains(method) {
+            return false;
+        }
+
+        let path = trim_slash(path);
+        match &self.pattern {
+            Pattern::Exact(p) => trim_slash(p) == path,
+            Pattern::Prefix(p) => {
+                let p = trim_slash(p);
+                p == "/"
+                    || path
+                        .strip_prefix(p)
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            }
+            Pattern::Glob(p) => glob(trim_slash(p).as_bytes(), path.as_bytes()),
+            Pattern::Route(p) => match matched {
+                Some(matched) => trim_slash(p) == trim_slash(matched),
+                None => route(trim_slash(p), path),
+            },
+        }
+    }
+}
+
+impl From<&str> for Rule {
+    fn from(value: &str) -> Self {
+        let value = value.trim();
+        let (method, path) = match value.split_once(' ') {
+            Some((method, path)) => match method.parse::<Method>() {
+                Ok(method) => (Some(method), path.trim()),
+                Err(_) => (None, value),
+            },
+            None => (None, value),
+        };
+
+        // 路由模板参数: `:name` 或 `*name`
+        let is_route = path.split('/').any(|segment| {
+            let mut chars = segment.chars();
+            match chars.next() {
+                Some(':') => true,
+                Some('*') => chars.next().is_some_and(char::is_alphabetic),
+                _ => false,
+            }
+        });
+
+        let rule = if is_route {
+            Self::route(path)
+        } else if path.contains('*') {
+            Self::glob(path)
+        } else {
+            Self::exact(path)
+        };
+
+        match method {
+            Some(method) => rule.method(method),
+            None => rule,
+        }
+    }
+}
+
+/// 路径过滤器 任意一条规则匹配即匹配
+///
+/// # Examples
+/// ```
+/// use mll_axum_utils::middleware::filter::{PathFilter, Rule};
+/// let filter = PathFilter::from(vec!["/login", "GET /articles/*"]).rule(Rule::prefix("/public"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PathFilter {
+    rules: Vec<Rule>,
+}
+
+impl PathFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加规则
+    pub fn rule<R: Into<Rule>>(mut self, rule: R) -> Self {
+        self.rules.push(rule.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// 请求是否匹配
+    pub fn matches<B>(&self, req: &Request<B>) -> bool {
+        let matched = req.extensions().get::<MatchedPath>();
+        let matched = matched.map(|m| m.as_str());
+        self.rules
+            .iter()
+            .any(|rule| rule.matches(req.method(), req.uri().path(), matched))
+    }
+}
+
+impl<R: Into<Rule>> From<Vec<R>> for PathFilter {
+    fn from(rules: Vec<R>) -> Self {
+        Self {
+            rules: rules.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// 过滤器的作用方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FilterMode {
+    /// 匹配的路由跳过中间件 其余路由生效
+    #[default]
+    Exempt,
+    /// 仅匹配的路由生效 其余路由跳过中间件
+    Protect,
+}
+
+impl FilterMode {
+    /// 中间件是否作用于该请求
+    pub fn applies<B>(&self, filter: &PathFilter, req: &Request<B>) -> bool {
+        match self {
+            FilterMode::Exempt => !filter.matches(req),
+            FilterMode::Protect => filter.matches(req),
+        }
+    }
+}
+
+/// 去除末尾的 `/` 根路径保留
+fn trim_slash(path: &str) -> &str {
+    match path.trim_end_matches('/') {
+        "" => "/",
+        path => path,
+    }
+}
+
+/// 通配符匹配 按模式从后向前动态规划 耗时为模式长度与路径长度之积
+fn glob(pattern: &[u8], path: &[u8]) -> bool {
+    let n = path.len();
+    // rows[i % 4][j]: pattern[i..] 是否匹配 path[j..] 只需保留后三行
+    let mut rows = vec![vec![false; n + 1]; 4];
+    for i in (0..=pattern.len()).rev() {
+        let mut row = std::mem::take(&mut rows[i % 4]);
+        let next = |k: usize, j: usize| rows[(i + k) % 4][j];
+        for j in (0..=n).rev() {
+            row[j] = match &pattern[i..] {
+                [] => j == n,
+                // `/**` 可以匹配零个路径段
+                [b'/', b'*', b'*', ..] => {
+                    next(3, j) || (path.get(j) == Some(&b'/') && next(1, j + 1))
+                }
+                [b'*', b'*', ..] => next(2, j) || (j < n && row[j + 1]),
+                [b'*', ..] => next(1, j) || (j < n && path[j] != b'/' && row[j + 1]),
+                [c, ..] => path.get(j) == Some(c) && next(1, j + 1),
+            };
+        }
+        rows[i % 4] = row;
+    }
+    rows[0][0]
+}
+
+/// 按路由模板匹配路径 `:name` 匹配一个路径段 `*name` 匹配剩余路径
+fn route(template: &str, path: &str) -> bool {
+    let mut path = path.split('/');
+    for segment in template.split('/') {
+        if segment.starts_with('*') {
+            return true;
+        }
+        match path.next() {
+            Some(p) if segment.starts_with(':') => {
+                if p.is_empty() {
+                    return false;
+                }
+            }
+            Some(p) if p == segment => {}
+            _ => return false,
+        }
+    }
+    path.next().is_none()
+}
+
+#[test]
+fn rule_matches() {
+    let get = Method::GET;
+    let post = Method::POST;
+
+    assert!(Rule::from("/login").matches(&get, "/login/", None));
+    assert!(!Rule::from("/login").matches(&get, "/login/x", None));
+
+    assert!(Rule::prefix("/public").matches(&get, "/public", None));
+    assert!(Rule::prefix("/public/").matches(&get, "/public/a/b", None));
+    assert!(!Rule::prefix("/public").matches(&get, "/publicity", None));
+
+    assert!(Rule::from("/static/*.png").matches(&get, "/static/a.png", None));
+    assert!(!Rule::from("/static/*.png").matches(&get, "/static/a/b.png", None));
+    assert!(Rule::from("/static/**/*.png").matches(&get, "/static/a/b.png", None));
+    assert!(Rule::from("/static/**").matches(&get, "/static", None));
+    assert!(Rule::from("/a/**/b/*").matches(&get, "/a/x/y/b/c", None));
+    assert!(!Rule::from("/a/**/b/*").matches(&get, "/a/x/b/c/d", None));
+    // 回溯模式不会随路径长度指数增长
+    let path = format!("/{}", "a".repeat(4096));
+    assert!(!Rule::from("/a*a*a*a*a*a*a*a*b").matches(&get, &path, None));
+    assert!(!Rule::from("/**a**a**a**a**b").matches(&get, &path, None));
+
+    assert!(Rule::from("/share/:id").matches(&get, "/share/1", None));
+    assert!(!Rule::from("/share/:id").matches(&get, "/share/1/2", None));
+    assert!(!Rule::from("/share/:id").matches(&get, "/share/1", Some("/share/:id/edit")));
+    assert!(Rule::from("/files/*path").matches(&get, "/files/a/b", None));
+
+    assert!(Rule::from("GET /articles/*").matches(&get, "/articles/1", None));
+    assert!(!Rule::from("GET /articles/*").matches(&post, "/articles/1", None));
+}
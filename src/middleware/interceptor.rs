@@ -1,41 +1,72 @@
 use std::{
-    sync::Arc,
+    future::Future,
+    sync::{Arc, Mutex},
     task::{Context, Poll},
+    time::{Duration, Instant},
 };
-use std::net::SocketAddr;
 
-use axum::{body::Body, http::Request, response::Response};
-use axum::extract::ConnectInfo;
+use axum::{
+    async_trait,
+    body::Body,
+    extract::FromRequestParts,
+    http::{request::Parts, Extensions, Method, Request, Uri},
+    response::Response,
+};
 use axum::response::IntoResponse;
 use futures_util::future::BoxFuture;
 use tower::{Layer, Service};
 use crate::res::Res;
+use super::filter::{FilterMode, PathFilter};
+use super::ip::ClientIp;
 
 /// 前置拦截器
 type Before<T> = fn(store: Arc<T>, req: &mut Request<Body>) -> Result<(), Response>;
 /// 后置拦截器
 type After<T> = fn(store: Arc<T>, &mut Response);
 
+/// 异步前置拦截器
+type BeforeHook<T> = Arc<
+    dyn Fn(
+            Arc<T>,
+            InterceptContext,
+            Request<Body>,
+        ) -> BoxFuture<'static, Result<Request<Body>, Response>>
+        + Send
+        + Sync,
+>;
+/// 异步后置拦截器
+type AfterHook<T> =
+    Arc<dyn Fn(Arc<T>, InterceptContext, Response) -> BoxFuture<'static, Response> + Send + Sync>;
+/// 已绑定 store 的前置拦截器
+type LinkBefore = Arc<
+    dyn Fn(InterceptContext, Request<Body>) -> BoxFuture<'static, Result<Request<Body>, Response>>
+        + Send
+        + Sync,
+>;
+/// 已绑定 store 的后置拦截器
+type LinkAfter =
+    Arc<dyn Fn(InterceptContext, Response) -> BoxFuture<'static, Response> + Send + Sync>;
+
 /// 拦截器
 ///
 /// before 前置拦截器：可以修改请求体和拒绝请求
 ///
 /// after  后置拦截器：可以修改响应体
 ///
+/// 需要异步 共享上下文或限定路由时使用 `AsyncInterceptor`
+///
 /// Examples
 /// ```no_run
-/// use std::net::SocketAddr;
 /// use std::sync::Arc;
 /// use axum::body::Body;
-/// use axum::extract::ConnectInfo;
 /// use axum::{http::Request,response::{Response,IntoResponse}};
-/// use mll_axum_utils::middleware::interceptor::Interceptor;
+/// use mll_axum_utils::middleware::{interceptor::Interceptor, ip::ClientIp};
 /// use mll_axum_utils::res::Res;
 /// /// 拒绝黑名单 ip 访问
-/// pub fn blacklist_ip(blacklist: Vec<&str>) -> Interceptor<Vec<&str>> {
+/// pub fn blacklist_ip(blacklist: Vec<&'static str>) -> Interceptor<Vec<&'static str>> {
 ///     fn handler(store: Arc<Vec<&str>>, req: &mut Request<Body>) -> Result<(), Response>{
-///         if let Some(info) = req.extensions().get::<ConnectInfo<SocketAddr>>() {
-///             if store.contains(&info.ip().to_string().as_str()) {
+///         if let Some(ClientIp(ip)) = ClientIp::from_extensions(req.extensions()) {
+///             if store.contains(&ip.to_string().as_str()) {
 ///                 return Err(Res::<()>::reject("").into_response());
 ///             }
 ///         }
@@ -57,7 +88,6 @@ impl<T> Interceptor<T> {
     }
 }
 
-
 impl<S, T> Layer<S> for Interceptor<T> {
     type Service = InterceptorService<S, T>;
 
@@ -117,15 +147,611 @@ impl<S, T> Service<Request<Body>> for InterceptorService<S, T>
     }
 }
 
+/// 异步拦截器
+///
+/// 同一请求的 before 与 after 共享 `InterceptContext` 可在 before 中写入数据供 after 读取
+///
+/// Examples
+/// ```no_run
+/// use std::sync::Arc;
+/// use axum::{body::Body, http::Request, response::Response};
+/// use mll_axum_utils::{log::Log, middleware::interceptor::{AsyncInterceptor, InterceptContext}};
+///
+/// struct Config {
+///     slow_ms: u128,
+/// }
+///
+/// let slow_log = AsyncInterceptor::with_store(Config { slow_ms: 500 })
+///     .before(|_: Arc<Config>, ctx: InterceptContext, req: Request<Body>| async move {
+///         ctx.insert(req.headers().contains_key("X-Debug"));
+///         Ok(req)
+///     })
+///     .after(|config: Arc<Config>, ctx: InterceptContext, res: Response| async move {
+///         let debug = ctx.get::<bool>().unwrap_or_default();
+///         if debug || ctx.elapsed().as_millis() > config.slow_ms {
+///             Log::warn(format!("{} {} 耗时 {:?}", ctx.method(), ctx.uri(), ctx.elapsed()));
+///         }
+///         res
+///     });
+/// ```
+pub struct AsyncInterceptor<T> {
+    store: Arc<T>,
+    before: Option<BeforeHook<T>>,
+    after: Option<AfterHook<T>>,
+    filter: Arc<PathFilter>,
+    mode: FilterMode,
+    order: i32,
+}
+
+impl<T> Clone for AsyncInterceptor<T> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            before: self.before.clone(),
+            after: self.after.clone(),
+            filter: self.filter.clone(),
+            mode: self.mode,
+            order: self.order,
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> AsyncInterceptor<T> {
+    /// 不含拦截器 通过 `before` `after` 添加
+    pub fn with_store(store: T) -> Self {
+        Self::from_arc(Arc::new(store))
+    }
+
+    fn from_arc(store: Arc<T>) -> Self {
+        Self {
+            store,
+            before: None,
+            after: None,
+            filter: Arc::new(PathFilter::new()),
+            mode: FilterMode::Exempt,
+            order: 0,
+        }
+    }
+
+    /// 仅匹配的路由执行拦截器
+    pub fn only(mut self, filter: PathFilter) -> Self {
+        self.filter = Arc::new(filter);
+        self.mode = FilterMode::Protect;
+        self
+    }
+
+    /// 匹配的路由跳过拦截器
+    pub fn exempt(mut self, filter: PathFilter) -> Self {
+        self.filter = Arc::new(filter);
+        self.mode = FilterMode::Exempt;
+        self
+    }
+
+    /// 在 `InterceptorChain` 中的顺序 越小越先执行前置拦截 越后执行后置拦截 默认0
+    ///
+    /// 顺序相同时按添加顺序
+    pub fn order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// 异步前置拦截器 返回请求继续处理 返回响应拒绝请求
+    pub fn before<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(Arc<T>, InterceptContext, Request<Body>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Request<Body>, Response>> + Send + 'static,
+    {
+        self.before = Some(Arc::new(move |store, ctx, req| Box::pin(f(store, ctx, req))));
+        self
+    }
+
+    /// 异步后置拦截器 返回修改后的响应
+    pub fn after<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(Arc<T>, InterceptContext, Response) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Response> + Send + 'static,
+    {
+        self.after = Some(Arc::new(move |store, ctx, res| Box::pin(f(store, ctx, res))));
+        self
+    }
+}
+
+/// 同步拦截器转为异步拦截器 以便限定路由或加入 `InterceptorChain`
+impl<T: Send + Sync + 'static> From<Interceptor<T>> for AsyncInterceptor<T> {
+    fn from(interceptor: Interceptor<T>) -> Self {
+        let mut async_interceptor = Self::from_arc(interceptor.store);
+        if let Some(f) = interceptor.before {
+            async_interceptor = async_interceptor.before(move |store, _, mut req| async move {
+                f(store, &mut req).map(|_| req)
+            });
+        }
+        if let Some(f) = interceptor.after {
+            async_interceptor = async_interceptor.after(move |store, _, mut res| async move {
+                f(store, &mut res);
+                res
+            });
+        }
+        async_interceptor
+    }
+}
+
+impl<S, T> Layer<S> for AsyncInterceptor<T> {
+    type Service = AsyncInterceptorService<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AsyncInterceptorService {
+            inner,
+            store: self.store.clone(),
+            before: self.before.clone(),
+            after: self.after.clone(),
+            filter: self.filter.clone(),
+            mode: self.mode,
+        }
+    }
+}
+
+/// 异步拦截器服务
+pub struct AsyncInterceptorService<S, T> {
+    inner: S,
+    store: Arc<T>,
+    before: Option<BeforeHook<T>>,
+    after: Option<AfterHook<T>>,
+    filter: Arc<PathFilter>,
+    mode: FilterMode,
+}
+
+impl<S: Clone, T> Clone for AsyncInterceptorService<S, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            store: self.store.clone(),
+            before: self.before.clone(),
+            after: self.after.clone(),
+            filter: self.filter.clone(),
+            mode: self.mode,
+        }
+    }
+}
+
+impl<S, T> Service<Request<Body>> for AsyncInterceptorService<S, T>
+    where
+        T: Sync + Send + 'static,
+        S: Service<Request<Body>, Response=Response> + Clone + Send + 'static,
+        S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        if !self.mode.applies(&self.filter, &req) {
+            return Box::pin(self.inner.call(req));
+        }
+
+        let store = self.store.clone();
+        let before = self.before.clone();
+        let after = self.after.clone();
+        let ctx = InterceptContext::new(&req);
+        req.extensions_mut().insert(ctx.clone());
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            // 执行前置拦截 拒绝请求时直接返回
+            let req = match before {
+                Some(f) => match f(store.clone(), ctx.clone(), req).await {
+                    Ok(req) => req,
+                    Err(err_res) => return Ok(err_res),
+                },
+                None => req,
+            };
+
+            let response = inner.call(req).await?;
+            // 执行后置拦截
+            match after {
+                Some(f) => Ok(f(store, ctx, response).await),
+                None => Ok(response),
+            }
+        })
+    }
+}
+
+/// 拦截器链 作为一个中间件执行多个拦截器
+///
+/// 前置拦截器按 `order` 依次执行 任一拒绝时跳过后续前置拦截器与 handler
+/// 后置拦截器按相反顺序执行 只执行前置拦截已通过的拦截器
+///
+/// 各拦截器可通过 `only` `exempt` 限定路由 链中共享同一个 `InterceptContext`
+///
+/// # Examples
+/// ```ignore
+/// let chain = InterceptorChain::new()
+///     .with(AsyncInterceptor::from(blacklist_ip(["10.0.0.8"])).order(-10))
+///     .with(audit.only(PathFilter::from(vec!["POST /admin/**", "DELETE /admin/**"])))
+///     .with(slow_log.exempt(PathFilter::from(vec!["/health"])));
+///
+/// let app = Router::new().route("/admin/users", post(create_user)).layer(chain);
+/// ```
+#[derive(Clone, Default)]
+pub struct InterceptorChain {
+    links: Vec<Arc<Link>>,
+}
+
+/// 链中的拦截器
+struct Link {
+    before: Option<LinkBefore>,
+    after: Option<LinkAfter>,
+    filter: Arc<PathFilter>,
+    mode: FilterMode,
+    order: i32,
+}
+
+impl InterceptorChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加拦截器 同步拦截器会转为 `AsyncInterceptor`
+    pub fn with<T, I>(mut self, interceptor: I) -> Self
+    where
+        T: Send + Sync + 'static,
+        I: Into<AsyncInterceptor<T>>,
+    {
+        let AsyncInterceptor { store, before, after, filter, mode, order } = interceptor.into();
+        let before = before.map(|f| {
+            let store = store.clone();
+            Arc::new(move |ctx, req| f(store.clone(), ctx, req)) as LinkBefore
+        });
+        let after = after.map(|f| {
+            let store = store.clone();
+            Arc::new(move |ctx, res| f(store.clone(), ctx, res)) as LinkAfter
+        });
+        self.links.push(Arc::new(Link { before, after, filter, mode, order }));
+        // 稳定排序 顺序相同时保持添加顺序
+        self.links.sort_by_key(|link| link.order);
+        self
+    }
+}
+
+impl<S> Layer<S> for InterceptorChain {
+    type Service = InterceptorChainService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        InterceptorChainService {
+            inner,
+            links: self.links.clone(),
+        }
+    }
+}
+
+/// 拦截器链服务
+#[derive(Clone)]
+pub struct InterceptorChainService<S> {
+    inner: S,
+    links: Vec<Arc<Link>>,
+}
+
+impl<S> Service<Request<Body>> for InterceptorChainService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        // 按原始请求确定作用的拦截器
+        let links: Vec<_> = self
+            .links
+            .iter()
+            .filter(|link| link.mode.applies(&link.filter, &req))
+            .cloned()
+            .collect();
+        if links.is_empty() {
+            return Box::pin(self.inner.call(req));
+        }
+
+        let ctx = InterceptContext::new(&req);
+        req.extensions_mut().insert(ctx.clone());
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let (passed, mut response) = match run_before(&links, &ctx, req).await {
+                Ok(req) => (links.len(), inner.call(req).await?),
+                Err((passed, err_res)) => (passed, err_res),
+            };
+
+            for link in links[..passed].iter().rev() {
+                if let Some(f) = &link.after {
+                    response = f(ctx.clone(), response).await;
+                }
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// 依次执行前置拦截器 拒绝时返回已通过的拦截器数量与响应
+async fn run_before(
+    links: &[Arc<Link>],
+    ctx: &InterceptContext,
+    mut req: Request<Body>,
+) -> Result<Request<Body>, (usize, Response)> {
+    for (i, link) in links.iter().enumerate() {
+        if let Some(f) = &link.before {
+            req = f(ctx.clone(), req).await.map_err(|err_res| (i, err_res))?;
+        }
+    }
+    Ok(req)
+}
+
+/// 拦截上下文 同一请求的前置与后置拦截器共享
+///
+/// 也会放入请求扩展 handler 中可直接提取
+#[derive(Clone)]
+pub struct InterceptContext(Arc<ContextInner>);
+
+struct ContextInner {
+    method: Method,
+    uri: Uri,
+    started: Instant,
+    data: Mutex<Extensions>,
+}
+
+impl InterceptContext {
+    fn new(req: &Request<Body>) -> Self {
+        Self(Arc::new(ContextInner {
+            method: req.method().clone(),
+            uri: req.uri().clone(),
+            started: Instant::now(),
+            data: Mutex::new(Extensions::new()),
+        }))
+    }
+
+    /// 请求方式
+    pub fn method(&self) -> &Method {
+        &self.0.method
+    }
+
+    /// 请求地址
+    pub fn uri(&self) -> &Uri {
+        &self.0.uri
+    }
+
+    /// 请求开始时间
+    pub fn started(&self) -> Instant {
+        self.0.started
+    }
+
+    /// 请求开始至今的耗时
+    pub fn elapsed(&self) -> Duration {
+        self.0.started.elapsed()
+    }
+
+    /// 写入数据 同类型数据会被覆盖
+    pub fn insert<V: Clone + Send + Sync + 'static>(&self, value: V) {
+        self.data().insert(value);
+    }
+
+    /// 读取数据
+    pub fn get<V: Clone + Send + Sync + 'static>(&self) -> Option<V> {
+        self.data().get::<V>().cloned()
+    }
+
+    /// 取出数据
+    pub fn remove<V: Send + Sync + 'static>(&self) -> Option<V> {
+        self.data().remove::<V>()
+    }
+
+    fn data(&self) -> std::sync::MutexGuard<'_, Extensions> {
+        self.0.data.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for InterceptContext
+where
+    S: Send + Sync,
+{
+    type Rejection = Res<()>;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<InterceptContext>()
+            .cloned()
+            .ok_or_else(|| Res::internal_error("未配置 Interceptor"))
+    }
+}
+
 /// 拒绝黑名单 ip 访问
-pub fn blacklist_ip(blacklist: Vec<&str>) -> Interceptor<Vec<&str>> {
-    fn handler(store: Arc<Vec<&str>>, req: &mut Request<Body>) -> Result<(), Response> {
-        if let Some(info) = req.extensions().get::<ConnectInfo<SocketAddr>>() {
-            if store.contains(&info.ip().to_string().as_str()) {
+#[deprecated(note = "使用支持网段与运行时修改的 `middleware::ip::IpAcl`")]
+pub fn blacklist_ip(blacklist: impl IntoIterator<Item = impl Into<String>>) -> Interceptor<Vec<String>> {
+    fn handler(store: Arc<Vec<String>>, req: &mut Request<Body>) -> Result<(), Response> {
+        if let Some(ClientIp(ip)) = ClientIp::from_extensions(req.extensions()) {
+            if store.contains(&ip.to_string()) {
                 return Err(Res::<()>::reject("").into_response());
             }
         }
         Ok(())
     }
-    Interceptor { store: Arc::new(blacklist), before: Some(handler), after: None }
-}
\ No newline at end of file
+    Interceptor::new(blacklist.into_iter().map(Into::into).collect(), Some(handler), None)
+}
+
+#[test]
+fn interceptor_hooks() {
+    use axum::{
+        http::{HeaderValue, StatusCode},
+        routing::get,
+        Router,
+    };
+    use tower::ServiceExt;
+
+    #[allow(clippy::result_large_err)]
+    fn reject(_: Arc<String>, req: &mut Request<Body>) -> Result<(), Response> {
+        match req.headers().contains_key("x-block") {
+            true => Err(Res::<()>::reject("").into_response()),
+            false => Ok(()),
+        }
+    }
+    fn mark(store: Arc<String>, res: &mut Response) {
+        let value = HeaderValue::from_str(&store).unwrap();
+        res.headers_mut().insert("x-mark", value);
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let sync = Router::new()
+            .route("/", get(|| async {}))
+            .layer(Interceptor {
+                store: Arc::new("sync".to_string()),
+                before: Some(reject),
+                after: Some(mark),
+            });
+        let res = sync
+            .clone()
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.headers()["x-mark"], "sync");
+        let req = Request::get("/")
+            .header("x-block", "1")
+            .body(Body::empty())
+            .unwrap();
+        let res = sync.oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        assert!(!res.headers().contains_key("x-mark"));
+
+        // before 写入的数据 after 与 handler 都能读取
+        let tagged = AsyncInterceptor::with_store(())
+            .before(|_, ctx: InterceptContext, req| async move {
+                ctx.insert(ctx.uri().path().to_string());
+                Ok(req)
+            })
+            .after(|_, ctx: InterceptContext, mut res: Response| async move {
+                let path = ctx.get::<String>().unwrap_or_default();
+                res.headers_mut()
+                    .insert("x-path", HeaderValue::try_from(path).unwrap());
+                res
+            })
+            .only(PathFilter::from(vec!["/api/**"]));
+        let app = Router::new()
+            .route(
+                "/api/a",
+                get(|ctx: InterceptContext| async move { ctx.get::<String>().unwrap() }),
+            )
+            .route("/b", get(|| async {}))
+            .layer(tagged);
+        let res = app
+            .clone()
+            .oneshot(Request::get("/api/a").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.headers()["x-path"], "/api/a");
+        let mut body = res.into_body();
+        let body = axum::body::HttpBody::data(&mut body)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(body, "/api/a");
+        // 不匹配的路由不执行拦截器
+        let res = app
+            .oneshot(Request::get("/b").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(!res.headers().contains_key("x-path"));
+    });
+}
+
+#[test]
+fn interceptor_chain_order() {
+    use axum::{http::StatusCode, routing::get, Router};
+    use tower::ServiceExt;
+
+    type Trace = Arc<Mutex<Vec<String>>>;
+    let trace = Trace::default();
+    let link = |name: &'static str| {
+        AsyncInterceptor::with_store((trace.clone(), name))
+            .before(
+                |store: Arc<(Trace, &str)>, _, req: Request<Body>| async move {
+                    let (trace, name) = &*store;
+                    trace.lock().unwrap().push(format!("before {name}"));
+                    if *name == "c" && req.headers().contains_key("x-block") {
+                        return Err(Res::<()>::reject("").into_response());
+                    }
+                    Ok(req)
+                },
+            )
+            .after(|store: Arc<(Trace, &str)>, _, res| async move {
+                let (trace, name) = &*store;
+                trace.lock().unwrap().push(format!("after {name}"));
+                res
+            })
+    };
+    // 添加顺序与执行顺序无关 顺序相同时按添加顺序
+    let chain = InterceptorChain::new()
+        .with(link("c").order(5))
+        .with(link("b").only(PathFilter::from(vec!["/api/**"])))
+        .with(link("a").order(-1))
+        .with(link("d").order(5));
+    let handler_trace = trace.clone();
+    let app = Router::new()
+        .route(
+            "/api/x",
+            get(move || async move { handler_trace.lock().unwrap().push("handler".into()) }),
+        )
+        .route("/x", get(|| async {}))
+        .layer(chain);
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let run = |req: Request<Body>| {
+            let app = app.clone();
+            let trace = trace.clone();
+            async move {
+                let status = app.oneshot(req).await.unwrap().status();
+                (status, std::mem::take(&mut *trace.lock().unwrap()))
+            }
+        };
+
+        let (status, steps) = run(Request::get("/api/x").body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            steps,
+            [
+                "before a", "before b", "before c", "before d", "handler", "after d", "after c",
+                "after b", "after a"
+            ]
+        );
+
+        // 不匹配的路由跳过 b
+        let (_, steps) = run(Request::get("/x").body(Body::empty()).unwrap()).await;
+        assert_eq!(
+            steps,
+            ["before a", "before c", "before d", "after d", "after c", "after a"]
+        );
+
+        // c 拒绝时跳过后续拦截器与 handler 只执行已通过的 after
+        let req = Request::get("/api/x").header("x-block", "1");
+        let (status, steps) = run(req.body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(
+            steps,
+            ["before a", "before b", "before c", "after b", "after a"]
+        );
+    });
+}
@@ -0,0 +1,280 @@
+use std::{
+    net::IpAddr,
+    sync::{Arc, RwLock},
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    http::Request,
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use tower::{Layer, Service};
+
+use super::{Cidr, ClientIp};
+use crate::{
+    middleware::filter::{FilterMode, PathFilter},
+    res::Res,
+};
+
+/// IP 访问控制中间件
+///
+/// 命中拒绝列表的地址拒绝访问 允许列表不为空时仅允许列表内的地址访问 拒绝优先于允许
+///
+/// 位于代理之后时配合 `ClientIpLayer` 使用
+///
+/// # Examples
+/// ```ignore
+/// let acl = IpAcl::new()
+///     .allow("10.0.0.0/8")
+///     .allow("fd00::/8")
+///     .deny("10.0.13.0/24")
+///     .only(PathFilter::new().rule(Rule::prefix("/admin")));
+/// let handle = acl.handle();
+///
+/// let app = Router::new()
+///     .route("/admin/ban", post(ban))
+///     .layer(acl)
+///     .layer(Extension(handle));
+///
+/// async fn ban(Extension(acl): Extension<IpAclHandle>, body: String) -> utils::Result<()> {
+///     acl.deny(&body)?;
+///     Ok(Res::ok(()))
+/// }
+/// ```
+#[derive(Clone, Default)]
+pub struct IpAcl {
+    handle: IpAclHandle,
+    filter: Arc<PathFilter>,
+    mode: FilterMode,
+}
+
+impl IpAcl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加允许的网段 如 `10.0.0.0/8` `::1`
+    ///
+    /// # Panics
+    /// 网段无效时 panic 网段来自外部输入时使用返回错误的 `IpAclHandle::allow`
+    pub fn allow(self, cidr: &str) -> Self {
+        self.handle.allow(cidr).expect("IpAcl 允许列表包含无效网段");
+        self
+    }
+
+    /// 添加拒绝的网段
+    ///
+    /// # Panics
+    /// 网段无效时 panic 网段来自外部输入时使用返回错误的 `IpAclHandle::deny`
+    pub fn deny(self, cidr: &str) -> Self {
+        self.handle.deny(cidr).expect("IpAcl 拒绝列表包含无效网段");
+        self
+    }
+
+    /// 仅匹配的路由检查
+    pub fn only(mut self, filter: PathFilter) -> Self {
+        self.filter = Arc::new(filter);
+        self.mode = FilterMode::Protect;
+        self
+    }
+
+    /// 匹配的路由跳过检查
+    pub fn exempt(mut self, filter: PathFilter) -> Self {
+        self.filter = Arc::new(filter);
+        self.mode = FilterMode::Exempt;
+        self
+    }
+
+    /// 运行时修改列表的句柄
+    pub fn handle(&self) -> IpAclHandle {
+        self.handle.clone()
+    }
+}
+
+/// IP 访问控制列表 与创建它的 `IpAcl` 共享 修改立即生效
+#[derive(Debug, Clone, Default)]
+pub struct IpAclHandle(Arc<RwLock<Lists>>);
+
+#[derive(Debug, Default)]
+struct Lists {
+    allow: Vec<Cidr>,
+    deny: Vec<Cidr>,
+}
+
+impl IpAclHandle {
+    /// 添加允许的网段
+    pub fn allow(&self, cidr: &str) -> Result<(), Res<()>> {
+        let cidr = cidr.parse()?;
+        let mut lists = self.write();
+        if !lists.allow.contains(&cidr) {
+            lists.allow.push(cidr);
+        }
+        Ok(())
+    }
+
+    /// 添加拒绝的网段
+    pub fn deny(&self, cidr: &str) -> Result<(), Res<()>> {
+        let cidr = cidr.parse()?;
+        let mut lists = self.write();
+        if !lists.deny.contains(&cidr) {
+            lists.deny.push(cidr);
+        }
+        Ok(())
+    }
+
+    /// 移除允许的网段 返回是否存在
+    pub fn remove_allow(&self, cidr: &str) -> Result<bool, Res<()>> {
+        let cidr = cidr.parse::<Cidr>()?;
+        let mut lists = self.write();
+        let len = lists.allow.len();
+        lists.allow.retain(|c| *c != cidr);
+        Ok(lists.allow.len() != len)
+    }
+
+    /// 移除拒绝的网段 返回是否存在
+    pub fn remove_deny(&self, cidr: &str) -> Result<bool, Res<()>> {
+        let cidr = cidr.parse::<Cidr>()?;
+        let mut lists = self.write();
+        let len = lists.deny.len();
+        lists.deny.retain(|c| *c != cidr);
+        Ok(lists.deny.len() != len)
+    }
+
+    /// 允许列表
+    pub fn allowed(&self) -> Vec<Cidr> {
+        self.read().allow.clone()
+    }
+
+    /// 拒绝列表
+    pub fn denied(&self) -> Vec<Cidr> {
+        self.read().deny.clone()
+    }
+
+    /// 是否允许访问 无法获取地址时仅在允许列表为空时允许
+    pub fn permits(&self, ip: Option<IpAddr>) -> bool {
+        let lists = self.read();
+        match ip {
+            Some(ip) => {
+                !lists.deny.iter().any(|c| c.contains(ip))
+                    && (lists.allow.is_empty() || lists.allow.iter().any(|c| c.contains(ip)))
+            }
+            None => lists.allow.is_empty(),
+        }
+    }
+
+    fn read(&self) -> std::sync::RwLockReadGuard<'_, Lists> {
+        self.0.read().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn write(&self) -> std::sync::RwLockWriteGuard<'_, Lists> {
+        self.0.write().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<S> Layer<S> for IpAcl {
+    type Service = IpAclService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        IpAclService {
+            inner,
+            handle: self.handle.clone(),
+            filter: self.filter.clone(),
+            mode: self.mode,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct IpAclService<S> {
+    inner: S,
+    handle: IpAclHandle,
+    filter: Arc<PathFilter>,
+    mode: FilterMode,
+}
+
+impl<S> Service<Request<Body>> for IpAclService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        if self.mode.applies(&self.filter, &req) {
+            let ip = ClientIp::from_extensions(req.extensions()).map(|ip| ip.0);
+            if !self.handle.permits(ip) {
+                return Box::pin(async { Ok(Res::<()>::reject("禁止访问").into_response()) });
+            }
+        }
+        Box::pin(self.inner.call(req))
+    }
+}
+
+#[test]
+fn acl_lists() {
+    use axum::{http::StatusCode, routing::get, Router};
+    use tower::ServiceExt;
+
+    let ip = |s: &str| Some(s.parse::<IpAddr>().unwrap());
+
+    // 拒绝优先于允许 允许列表为空时允许所有未拒绝的地址
+    let acl = IpAcl::new().deny("10.0.13.0/24");
+    let handle = acl.handle();
+    assert!(handle.permits(ip("10.0.12.1")));
+    assert!(handle.permits(None));
+    let acl = acl.allow("10.0.0.0/8").allow("::1");
+    assert!(handle.permits(ip("10.0.12.1")));
+    assert!(handle.permits(ip("::1")));
+    assert!(!handle.permits(ip("10.0.13.1")));
+    assert!(!handle.permits(ip("192.168.0.1")));
+    assert!(!handle.permits(None));
+
+    // 运行时修改
+    assert!(handle.deny("10.0.13.0/33").is_err());
+    handle.deny("10.0.12.0/24").unwrap();
+    handle.deny("10.0.12.0/24").unwrap();
+    assert_eq!(handle.denied().len(), 2);
+    assert!(handle.remove_deny("10.0.13.0/24").unwrap());
+    assert!(!handle.remove_deny("10.0.13.0/24").unwrap());
+    assert!(handle.permits(ip("10.0.13.1")));
+    assert!(!handle.permits(ip("10.0.12.1")));
+    assert!(handle.remove_allow("10.0.0.0/8").unwrap());
+    assert!(!handle.permits(ip("10.0.13.1")));
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let app = Router::new()
+            .route("/", get(|| async {}))
+            .route("/health", get(|| async {}))
+            .layer(acl.exempt(PathFilter::from(vec!["/health"])));
+        let call = |uri: &'static str, addr: &str| {
+            let mut req = Request::get(uri).body(Body::empty()).unwrap();
+            req.extensions_mut().insert(ClientIp(addr.parse().unwrap()));
+            app.clone().oneshot(req)
+        };
+
+        assert_eq!(call("/", "::1").await.unwrap().status(), StatusCode::OK);
+        let res = call("/", "10.0.13.1").await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+        let res = call("/health", "10.0.13.1").await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+
+        // 通过句柄修改后立即生效
+        handle.allow("10.0.13.0/24").unwrap();
+        let res = call("/", "10.0.13.1").await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        handle.deny("::1").unwrap();
+        let res = call("/", "::1").await.unwrap();
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    });
+}
@@ -0,0 +1,368 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    net::IpAddr,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    http::{header::RETRY_AFTER, HeaderValue, Request},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use tower::{Layer, Service};
+
+use super::ClientIp;
+use crate::{
+    log::Log,
+    middleware::filter::{FilterMode, PathFilter},
+    res::Res,
+    utils::timestamp,
+};
+
+/// 按响应状态自动封禁 ip 的中间件
+///
+/// 在 `window` 内失败 `max_failures` 次后封禁 每次封禁时长翻倍 最长为 `max_ban`
+/// 被封禁的 ip 访问该中间件下的所有路由都会被拒绝 失败次数只统计 `only` / `exempt` 限定的路由
+///
+/// 位于代理之后时配合 `ClientIpLayer` 使用
+///
+/// # Examples
+/// ```ignore
+/// let ban = IpBan::new()
+///     .statuses([401, 422])
+///     .max_failures(5)
+///     .window(60)
+///     .ban(60 * 5)
+///     .only(PathFilter::from(vec!["POST /login"]));
+/// let handle = ban.handle();
+///
+/// let app = Router::new()
+///     .route("/login", post(login))
+///     .layer(ban)
+///     .layer(ClientIpLayer::new().trust("10.0.0.0/8"));
+/// ```
+#[derive(Clone)]
+pub struct IpBan {
+    config: Arc<BanConfig>,
+    handle: IpBanHandle,
+    filter: Arc<PathFilter>,
+    mode: FilterMode,
+}
+
+struct BanConfig {
+    statuses: Vec<u16>,
+    max_failures: usize,
+    window: u64,
+    ban: u64,
+    max_ban: u64,
+}
+
+impl Default for IpBan {
+    fn default() -> Self {
+        let config = BanConfig {
+            statuses: vec![401, 403, 422],
+            max_failures: 10,
+            window: 60,
+            ban: 60 * 5,
+            max_ban: 60 * 60 * 24,
+        };
+        Self {
+            config: Arc::new(config),
+            handle: IpBanHandle::default(),
+            filter: Arc::new(PathFilter::new()),
+            mode: FilterMode::Exempt,
+        }
+    }
+}
+
+impl IpBan {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 计为失败的响应状态码 默认 401 403 422
+    pub fn statuses<I: IntoIterator<Item = u16>>(mut self, statuses: I) -> Self {
+        self.config_mut().statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// 触发封禁的失败次数 默认10次
+    pub fn max_failures(mut self, max_failures: usize) -> Self {
+        self.config_mut().max_failures = max_failures.max(1);
+        self
+    }
+
+    /// 统计失败次数的时间窗口 默认60s 单位 s
+    pub fn window(mut self, window: u64) -> Self {
+        self.config_mut().window = window;
+        self
+    }
+
+    /// 首次封禁时长 默认5分钟 单位 s
+    pub fn ban(mut self, ban: u64) -> Self {
+        self.config_mut().ban = ban;
+        self
+    }
+
+    /// 最长封禁时长 默认24小时 单位 s
+    ///
+    /// 解封后超过该时间未再被封禁时 封禁时长重新计算
+    pub fn max_ban(mut self, max_ban: u64) -> Self {
+        self.config_mut().max_ban = max_ban;
+        self
+    }
+
+    /// 仅统计匹配路由的失败
+    pub fn only(mut self, filter: PathFilter) -> Self {
+        self.filter = Arc::new(filter);
+        self.mode = FilterMode::Protect;
+        self
+    }
+
+    /// 不统计匹配路由的失败
+    pub fn exempt(mut self, filter: PathFilter) -> Self {
+        self.filter = Arc::new(filter);
+        self.mode = FilterMode::Exempt;
+        self
+    }
+
+    /// 查看与解除封禁的句柄
+    pub fn handle(&self) -> IpBanHandle {
+        self.handle.clone()
+    }
+
+    fn config_mut(&mut self) -> &mut BanConfig {
+        Arc::get_mut(&mut self.config).expect("IpBan 配置需在 layer 前完成")
+    }
+}
+
+/// 封禁状态 与创建它的 `IpBan` 共享
+#[derive(Debug, Clone, Default)]
+pub struct IpBanHandle(Arc<Mutex<HashMap<IpAddr, Entry>>>);
+
+#[derive(Debug, Default)]
+struct Entry {
+    /// 窗口内的失败时间
+    failures: VecDeque<u64>,
+    /// 已封禁次数
+    bans: u32,
+    /// 最近一次封禁的结束时间
+    banned_until: u64,
+    /// 是否处于封禁中 封禁结束后的首个请求时重置
+    active: bool,
+}
+
+/// 检查结果
+enum Check {
+    Allowed,
+    Banned(u64),
+    Unbanned,
+}
+
+impl IpBanHandle {
+    /// 当前被封禁的 ip 与封禁结束时间
+    pub fn banned(&self) -> Vec<(IpAddr, u64)> {
+        let now = timestamp();
+        self.lock()
+            .iter()
+            .filter(|(_, entry)| entry.active && entry.banned_until > now)
+            .map(|(ip, entry)| (*ip, entry.banned_until))
+            .collect()
+    }
+
+    /// 解除封禁并清除失败记录 返回是否存在记录
+    pub fn unban(&self, ip: IpAddr) -> bool {
+        let ip = super::canonical(ip);
+        let Some(entry) = self.lock().remove(&ip) else {
+            return false;
+        };
+        if entry.active && entry.banned_until > timestamp() {
+            Log::warn(format!("ip {ip} 已手动解除封禁"));
+        }
+        true
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<IpAddr, Entry>> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn check(&self, ip: IpAddr, now: u64) -> Check {
+        let mut entries = self.lock();
+        match entries.get_mut(&ip) {
+            Some(entry) if entry.active => {
+                if entry.banned_until > now {
+                    return Check::Banned(entry.banned_until - now);
+                }
+                entry.active = false;
+                entry.failures.clear();
+                Check::Unbanned
+            }
+            _ => Check::Allowed,
+        }
+    }
+
+    /// 记录失败 触发封禁时返回封禁时长
+    fn fail(&self, config: &BanConfig, ip: IpAddr, now: u64) -> Option<u64> {
+        let mut entries = self.lock();
+        // 新 ip 时顺带清除过期记录
+        if !entries.contains_key(&ip) {
+            entries.retain(|_, entry| !entry.expired(config, now));
+        }
+
+        let entry = entries.entry(ip).or_default();
+        // 封禁前已在处理中的请求不再计入 避免立即再次封禁
+        if entry.active && entry.banned_until > now {
+            return None;
+        }
+        // 距上次解封已超过最长封禁时长 重新计算封禁时长
+        if entry.bans > 0 && now >= entry.banned_until + config.max_ban {
+            entry.bans = 0;
+        }
+        while entry
+            .failures
+            .front()
+            .is_some_and(|at| at + config.window <= now)
+        {
+            entry.failures.pop_front();
+        }
+        entry.failures.push_back(now);
+        if entry.failures.len() < config.max_failures {
+            return None;
+        }
+
+        let ban = config
+            .ban
+            .saturating_mul(1u64.checked_shl(entry.bans).unwrap_or(u64::MAX))
+            .min(config.max_ban);
+        entry.failures.clear();
+        entry.bans = entry.bans.saturating_add(1);
+        entry.banned_until = now + ban;
+        entry.active = true;
+        Some(ban)
+    }
+}
+
+impl Entry {
+    /// 记录是否可清除
+    fn expired(&self, config: &BanConfig, now: u64) -> bool {
+        let last = self.failures.back().copied().unwrap_or(0);
+        now >= last + config.window && (self.bans == 0 || now >= self.banned_until + config.max_ban)
+    }
+}
+
+impl<S> Layer<S> for IpBan {
+    type Service = IpBanService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        IpBanService {
+            inner,
+            config: self.config.clone(),
+            handle: self.handle.clone(),
+            filter: self.filter.clone(),
+            mode: self.mode,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct IpBanService<S> {
+    inner: S,
+    config: Arc<BanConfig>,
+    handle: IpBanHandle,
+    filter: Arc<PathFilter>,
+    mode: FilterMode,
+}
+
+impl<S> Service<Request<Body>> for IpBanService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let Some(ClientIp(ip)) = ClientIp::from_extensions(req.extensions()) else {
+            return Box::pin(self.inner.call(req));
+        };
+
+        let now = timestamp();
+        let unbanned = match self.handle.check(ip, now) {
+            Check::Banned(remaining) => {
+                return Box::pin(async move { Ok(banned(remaining)) });
+            }
+            Check::Unbanned => true,
+            Check::Allowed => false,
+        };
+
+        let watch = self.mode.applies(&self.filter, &req);
+        let config = self.config.clone();
+        let handle = self.handle.clone();
+        let future = self.inner.call(req);
+        Box::pin(async move {
+            if unbanned {
+                Log::warn(format!("ip {ip} 封禁结束"));
+            }
+
+            let response = future.await?;
+            let failed = config.statuses.contains(&response.status().as_u16());
+            if watch && failed {
+                if let Some(ban) = handle.fail(&config, ip, timestamp()) {
+                    Log::warn(format!(
+                        "ip {ip} 在 {}s 内失败 {} 次 封禁 {ban}s",
+                        config.window, config.max_failures
+                    ));
+                }
+            }
+            Ok(response)
+        })
+    }
+}
+
+/// 封禁中的响应 `Retry-After` 为剩余封禁时长
+fn banned(remaining: u64) -> Response {
+    let mut res = Res::<()>::reject("访问已被暂时禁止").into_response();
+    res.headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(remaining));
+    res
+}
+
+#[test]
+fn ban_escalates() {
+    let ip = "1.2.3.4".parse().unwrap();
+    let config = BanConfig {
+        statuses: vec![401],
+        max_failures: 3,
+        window: 10,
+        ban: 60,
+        max_ban: 100,
+    };
+    let handle = IpBanHandle::default();
+
+    assert_eq!(handle.fail(&config, ip, 0), None);
+    assert_eq!(handle.fail(&config, ip, 5), None);
+    // 窗口外的失败不计入
+    assert_eq!(handle.fail(&config, ip, 12), None);
+    assert_eq!(handle.fail(&config, ip, 13), Some(60));
+    // 封禁期间的失败不计入
+    for now in 14..20 {
+        assert_eq!(handle.fail(&config, ip, now), None);
+    }
+    assert!(matches!(handle.check(ip, 72), Check::Banned(1)));
+    assert!(matches!(handle.check(ip, 73), Check::Unbanned));
+
+    for now in 80..82 {
+        assert_eq!(handle.fail(&config, ip, now), None);
+    }
+    // 第二次封禁时长翻倍 不超过最长封禁时长
+    assert_eq!(handle.fail(&config, ip, 82), Some(100));
+    assert!(handle.unban(ip));
+    assert!(matches!(handle.check(ip, 83), Check::Allowed));
+}
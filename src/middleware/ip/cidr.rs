@@ -0,0 +1,145 @@
+use std::{
+    fmt::{self, Display},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+use crate::res::Res;
+
+/// IPv4 / IPv6 网段
+///
+/// IPv4 映射的 IPv6 地址 `::ffff:a.b.c.d` 按 IPv4 处理
+///
+/// # Examples
+/// ```
+/// use mll_axum_utils::middleware::ip::Cidr;
+/// let cidr: Cidr = "10.0.0.0/8".parse().unwrap();
+/// assert!(cidr.contains("10.1.2.3".parse().unwrap()));
+/// assert!(cidr.contains("::ffff:10.1.2.3".parse().unwrap()));
+/// // 不带前缀长度时为单个地址
+/// let single: Cidr = "2001:db8::1".parse().unwrap();
+/// assert_eq!(single.to_string(), "2001:db8::1/128");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// 网段 主机位会被清零
+    pub fn new(addr: IpAddr, prefix: u8) -> Result<Self, Res<()>> {
+        let invalid = || Res::error(format!("无效的网段: {addr}/{prefix}"));
+        // IPv4 映射地址的前缀包含 96 位固定部分
+        let (ip, prefix) = match canonical(addr) {
+            ip if ip.is_ipv4() && addr.is_ipv6() => {
+                (ip, prefix.checked_sub(96).ok_or_else(invalid)?)
+            }
+            ip => (ip, prefix),
+        };
+        let max = if ip.is_ipv4() { 32 } else { 128 };
+        if prefix > max {
+            return Err(invalid());
+        }
+        Ok(Self {
+            addr: mask(ip, prefix),
+            prefix,
+        })
+    }
+
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    pub fn prefix(&self) -> u8 {
+        self.prefix
+    }
+
+    /// 是否包含该地址
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = canonical(ip);
+        ip.is_ipv4() == self.addr.is_ipv4() && mask(ip, self.prefix) == self.addr
+    }
+}
+
+impl From<IpAddr> for Cidr {
+    fn from(addr: IpAddr) -> Self {
+        let addr = canonical(addr);
+        let prefix = if addr.is_ipv4() { 32 } else { 128 };
+        Self { addr, prefix }
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = Res<()>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || Res::error(format!("无效的网段: {s}"));
+        match s.split_once('/') {
+            Some((addr, prefix)) => {
+                let addr = addr.parse().map_err(|_| invalid())?;
+                let prefix = prefix.parse().map_err(|_| invalid())?;
+                Self::new(addr, prefix)
+            }
+            None => s.parse::<IpAddr>().map(Self::from).map_err(|_| invalid()),
+        }
+    }
+}
+
+impl TryFrom<&str> for Cidr {
+    type Error = Res<()>;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// IPv4 映射的 IPv6 地址转为 IPv4
+pub fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => match v6.to_ipv4_mapped() {
+            Some(v4) => IpAddr::V4(v4),
+            None => ip,
+        },
+        ip => ip,
+    }
+}
+
+/// 清零主机位
+fn mask(ip: IpAddr, prefix: u8) -> IpAddr {
+    match ip {
+        IpAddr::V4(v4) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            IpAddr::V4(Ipv4Addr::from(u32::from(v4) & mask))
+        }
+        IpAddr::V6(v6) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            IpAddr::V6(Ipv6Addr::from(u128::from(v6) & mask))
+        }
+    }
+}
+
+#[test]
+fn cidr_contains() {
+    let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+    let cidr = |s: &str| s.parse::<Cidr>().unwrap();
+
+    assert!(cidr("10.0.0.0/8").contains(ip("10.255.0.1")));
+    assert!(!cidr("10.0.0.0/8").contains(ip("11.0.0.1")));
+    assert!(cidr("10.0.0.0/8").contains(ip("::ffff:10.0.0.1")));
+    assert!(cidr("::ffff:192.168.0.0/112").contains(ip("192.168.3.4")));
+    assert_eq!(cidr("192.168.1.77/24").to_string(), "192.168.1.0/24");
+    assert!(cidr("0.0.0.0/0").contains(ip("8.8.8.8")));
+    assert!(!cidr("0.0.0.0/0").contains(ip("2001:db8::1")));
+    assert!(cidr("2001:db8::/32").contains(ip("2001:db8:1::1")));
+    assert!(cidr("127.0.0.1").contains(ip("127.0.0.1")));
+    assert!("10.0.0.0/33".parse::<Cidr>().is_err());
+    assert!("::ffff:1.2.3.4/64".parse::<Cidr>().is_err());
+}
@@ -0,0 +1,252 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::{
+    async_trait,
+    body::Body,
+    extract::{ConnectInfo, FromRequestParts},
+    http::{request::Parts, Extensions, HeaderMap, Request},
+};
+use tower::{Layer, Service};
+
+use super::{canonical, Cidr};
+use crate::res::Res;
+
+/// 客户端地址 由 `ClientIpLayer` 按受信任代理解析
+///
+/// 未配置 `ClientIpLayer` 时为连接的对端地址
+///
+/// # Examples
+/// ```ignore
+/// async fn index(ClientIp(ip): ClientIp) -> String {
+///     ip.to_string()
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClientIp(pub IpAddr);
+
+impl ClientIp {
+    /// 从请求扩展读取 优先使用 `ClientIpLayer` 的解析结果 其次为 `ConnectInfo<SocketAddr>`
+    pub fn from_extensions(extensions: &Extensions) -> Option<Self> {
+        match extensions.get::<ClientIp>() {
+            Some(ip) => Some(*ip),
+            None => extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|info| Self(canonical(info.ip()))),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientIp
+where
+    S: Send + Sync,
+{
+    type Rejection = Res<()>;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Self::from_extensions(&parts.extensions)
+            .ok_or_else(|| Res::internal_error("无法获取客户端地址 未配置 ConnectInfo<SocketAddr>"))
+    }
+}
+
+/// 代理传递客户端地址的请求头
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum IpHeader {
+    /// `X-Forwarded-For: client, proxy1, proxy2`
+    #[default]
+    XForwardedFor,
+    /// `X-Real-IP: client`
+    XRealIp,
+    /// RFC 7239 `Forwarded: for=client, for="[2001:db8::1]:4711"`
+    Forwarded,
+}
+
+impl IpHeader {
+    /// 请求头中的地址 从客户端到最近的代理排列 无法解析的地址为 None
+    fn chain(&self, headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+        let name = match self {
+            IpHeader::XForwardedFor => "x-forwarded-for",
+            IpHeader::XRealIp => "x-real-ip",
+            IpHeader::Forwarded => "forwarded",
+        };
+        let values = headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok());
+
+        let mut chain = vec![];
+        for value in values {
+            for node in value.split(',') {
+                let node = match self {
+                    IpHeader::Forwarded => node.split(';').find_map(|pair| {
+                        let (key, value) = pair.split_once('=')?;
+                        key.trim().eq_ignore_ascii_case("for").then_some(value)
+                    }),
+                    _ => Some(node),
+                };
+                if let Some(node) = node.filter(|node| !node.trim().is_empty()) {
+                    chain.push(parse_node(node));
+                }
+            }
+        }
+        chain
+    }
+}
+
+/// 解析 `1.2.3.4` `1.2.3.4:80` `"[::1]:80"` 等形式的地址
+fn parse_node(node: &str) -> Option<IpAddr> {
+    let node = node.trim().trim_matches('"');
+    match node.parse::<IpAddr>() {
+        Ok(ip) => Some(ip),
+        Err(_) => match node.parse::<SocketAddr>() {
+            Ok(addr) => Some(addr.ip()),
+            Err(_) => node.strip_prefix('[')?.strip_suffix(']')?.parse().ok(),
+        },
+    }
+}
+
+/// 解析客户端真实地址的中间件 结果以 `ClientIp` 放入请求扩展
+///
+/// 仅当连接来自受信任代理时读取请求头 从右向左跳过受信任代理 第一个不受信任的地址即为客户端地址
+///
+/// 只读取配置的一个请求头 避免客户端伪造代理未设置的请求头
+///
+/// 需放在使用 `ClientIp` 的中间件 (`Logger` `IpAcl` 等) 外层
+///
+/// # Examples
+/// ```ignore
+/// let app = Router::new()
+///     .route("/", get(index))
+///     .layer(IpAcl::new().deny("203.0.113.0/24"))
+///     .layer(Logger::default())
+///     .layer(ClientIpLayer::new().trust("10.0.0.0/8").header(IpHeader::XForwardedFor));
+///
+/// axum::Server::bind(&addr)
+///     .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+///     .await?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ClientIpLayer {
+    trusted: Arc<Vec<Cidr>>,
+    header: IpHeader,
+}
+
+impl ClientIpLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加受信任的代理网段 网段无效时 panic
+    pub fn trust(mut self, cidr: &str) -> Self {
+        let cidr = cidr.parse().expect("ClientIpLayer 受信任代理包含无效网段");
+        Arc::make_mut(&mut self.trusted).push(cidr);
+        self
+    }
+
+    /// 读取的请求头 默认 `X-Forwarded-For`
+    pub fn header(mut self, header: IpHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    fn trusted(&self, ip: IpAddr) -> bool {
+        self.trusted.iter().any(|cidr| cidr.contains(ip))
+    }
+
+    /// 按对端地址与请求头解析客户端地址
+    pub fn resolve(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        let mut client = canonical(peer);
+        if !self.trusted(client) {
+            return client;
+        }
+
+        for hop in self.header.chain(headers).into_iter().rev() {
+            // 无法解析的地址之前的内容不可信
+            let Some(hop) = hop else {
+                break;
+            };
+            client = canonical(hop);
+            if !self.trusted(client) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+impl<S> Layer<S> for ClientIpLayer {
+    type Service = ClientIpService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientIpService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ClientIpService<S> {
+    inner: S,
+    layer: ClientIpLayer,
+}
+
+impl<S> Service<Request<Body>> for ClientIpService<S>
+where
+    S: Service<Request<Body>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let peer = req.extensions().get::<ConnectInfo<SocketAddr>>();
+        if let Some(peer) = peer.map(|info| info.ip()) {
+            let ip = self.layer.resolve(peer, req.headers());
+            req.extensions_mut().insert(ClientIp(ip));
+        }
+        self.inner.call(req)
+    }
+}
+
+#[test]
+fn client_ip_resolve() {
+    let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+    let headers = |name: &'static str, value: &'static str| {
+        let mut headers = HeaderMap::new();
+        headers.insert(name, value.parse().unwrap());
+        headers
+    };
+    let layer = ClientIpLayer::new().trust("10.0.0.0/8");
+    let xff = headers("x-forwarded-for", "6.6.6.6, 1.2.3.4, 10.0.0.2");
+
+    // 对端不受信任时忽略请求头
+    assert_eq!(layer.resolve(ip("5.5.5.5"), &xff), ip("5.5.5.5"));
+    // 跳过受信任代理 客户端伪造的最左侧地址不被采用
+    assert_eq!(layer.resolve(ip("::ffff:10.0.0.1"), &xff), ip("1.2.3.4"));
+    // 只读取配置的请求头
+    let real = headers("x-real-ip", "1.2.3.4");
+    assert_eq!(layer.resolve(ip("10.0.0.1"), &real), ip("10.0.0.1"));
+    let layer = layer.header(IpHeader::XRealIp);
+    assert_eq!(layer.resolve(ip("10.0.0.1"), &real), ip("1.2.3.4"));
+
+    let forwarded = headers(
+        "forwarded",
+        r#"for=6.6.6.6, for="[2001:db8::17]:4711";proto=https, for=10.0.0.3"#,
+    );
+    let layer = layer.header(IpHeader::Forwarded);
+    assert_eq!(
+        layer.resolve(ip("10.0.0.1"), &forwarded),
+        ip("2001:db8::17")
+    );
+    let unknown = headers("forwarded", "for=1.2.3.4, for=unknown, for=10.0.0.3");
+    assert_eq!(layer.resolve(ip("10.0.0.1"), &unknown), ip("10.0.0.3"));
+}
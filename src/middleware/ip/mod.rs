@@ -0,0 +1,9 @@
+mod acl;
+mod ban;
+mod cidr;
+mod client;
+
+pub use acl::*;
+pub use ban::*;
+pub use cidr::*;
+pub use client::*;
@@ -0,0 +1,301 @@
+use std::{
+    marker::PhantomData,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::{
+    async_trait,
+    body::Body,
+    extract::FromRequestParts,
+    http::{request::Parts, Request},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use tower::{Layer, Service};
+
+use super::JwtRejection;
+use crate::res::Res;
+
+/// claims 携带的角色与权限
+///
+/// # Examples
+/// ```ignore
+/// impl Authority for Claims {
+///     fn roles(&self) -> Vec<&str> {
+///         self.roles.iter().map(String::as_str).collect()
+///     }
+///
+///     fn scopes(&self) -> Vec<&str> {
+///         self.scope.split(' ').collect()
+///     }
+/// }
+/// ```
+pub trait Authority {
+    /// 角色
+    fn roles(&self) -> Vec<&str> {
+        vec![]
+    }
+
+    /// 权限
+    fn scopes(&self) -> Vec<&str> {
+        vec![]
+    }
+}
+
+/// 权限要求
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Require {
+    /// 拥有任一角色
+    AnyRole(Vec<String>),
+    /// 拥有所有角色
+    AllRoles(Vec<String>),
+    /// 拥有任一权限
+    AnyScope(Vec<String>),
+    /// 拥有所有权限
+    AllScopes(Vec<String>),
+}
+
+impl Require {
+    pub fn any_role<I: IntoIterator<Item = S>, S: Into<String>>(roles: I) -> Self {
+        Self::AnyRole(roles.into_iter().map(Into::into).collect())
+    }
+
+    pub fn all_roles<I: IntoIterator<Item = S>, S: Into<String>>(roles: I) -> Self {
+        Self::AllRoles(roles.into_iter().map(Into::into).collect())
+    }
+
+    pub fn any_scope<I: IntoIterator<Item = S>, S: Into<String>>(scopes: I) -> Self {
+        Self::AnyScope(scopes.into_iter().map(Into::into).collect())
+    }
+
+    pub fn all_scopes<I: IntoIterator<Item = S>, S: Into<String>>(scopes: I) -> Self {
+        Self::AllScopes(scopes.into_iter().map(Into::into).collect())
+    }
+
+    /// claims 是否满足要求
+    pub fn check<T: Authority>(&self, claims: &T) -> bool {
+        match self {
+            Require::AnyRole(required) => any(required, &claims.roles()),
+            Require::AllRoles(required) => all(required, &claims.roles()),
+            Require::AnyScope(required) => any(required, &claims.scopes()),
+            Require::AllScopes(required) => all(required, &claims.scopes()),
+        }
+    }
+}
+
+fn any(required: &[String], owned: &[&str]) -> bool {
+    required.iter().any(|r| owned.contains(&r.as_str()))
+}
+
+fn all(required: &[String], owned: &[&str]) -> bool {
+    required.iter().all(|r| owned.contains(&r.as_str()))
+}
+
+/// 权限验证失败
+enum Denied {
+    /// 未经过 `JwtAuth` 验证 401
+    Unauthenticated,
+    /// 权限不足 403
+    Forbidden,
+}
+
+impl IntoResponse for Denied {
+    fn into_response(self) -> Response {
+        match self {
+            Denied::Unauthenticated => JwtRejection::Missing.into_response(),
+            Denied::Forbidden => Res::<()>::reject("权限不足").into_response(),
+        }
+    }
+}
+
+/// 检查所有要求
+fn authorize<T: Authority>(claims: Option<&T>, requires: &[Require]) -> Result<(), Denied> {
+    let claims = claims.ok_or(Denied::Unauthenticated)?;
+    match requires.iter().all(|require| require.check(claims)) {
+        true => Ok(()),
+        false => Err(Denied::Forbidden),
+    }
+}
+
+/// 权限验证中间件 需在 `JwtAuth` 之后执行 从请求扩展中读取 claims
+///
+/// 多个要求需同时满足 未携带 claims 返回 401 权限不足返回 403
+///
+/// # Examples
+/// ```ignore
+/// let admin = Router::new()
+///     .route("/users", delete(delete_user))
+///     .route_layer(Authorize::<Claims>::any_role(["admin", "root"]));
+///
+/// let app = Router::new()
+///     .nest("/admin", admin)
+///     .layer(JwtAuth::<Claims>::new(vec!["/login"]));
+/// ```
+pub struct Authorize<T> {
+    requires: Arc<Vec<Require>>,
+    _claims: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Authorize<T> {
+    fn clone(&self) -> Self {
+        Self {
+            requires: self.requires.clone(),
+            _claims: PhantomData,
+        }
+    }
+}
+
+impl<T: Authority> Authorize<T> {
+    pub fn new(require: Require) -> Self {
+        Self {
+            requires: Arc::new(vec![require]),
+            _claims: PhantomData,
+        }
+    }
+
+    pub fn any_role<I: IntoIterator<Item = S>, S: Into<String>>(roles: I) -> Self {
+        Self::new(Require::any_role(roles))
+    }
+
+    pub fn all_roles<I: IntoIterator<Item = S>, S: Into<String>>(roles: I) -> Self {
+        Self::new(Require::all_roles(roles))
+    }
+
+    pub fn any_scope<I: IntoIterator<Item = S>, S: Into<String>>(scopes: I) -> Self {
+        Self::new(Require::any_scope(scopes))
+    }
+
+    pub fn all_scopes<I: IntoIterator<Item = S>, S: Into<String>>(scopes: I) -> Self {
+        Self::new(Require::all_scopes(scopes))
+    }
+
+    /// 追加需同时满足的要求
+    pub fn and(mut self, require: Require) -> Self {
+        Arc::make_mut(&mut self.requires).push(require);
+        self
+    }
+}
+
+impl<S, T> Layer<S> for Authorize<T> {
+    type Service = AuthorizeService<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AuthorizeService {
+            inner,
+            requires: self.requires.clone(),
+            _claims: PhantomData,
+        }
+    }
+}
+
+pub struct AuthorizeService<S, T> {
+    inner: S,
+    requires: Arc<Vec<Require>>,
+    _claims: PhantomData<fn() -> T>,
+}
+
+impl<S: Clone, T> Clone for AuthorizeService<S, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            requires: self.requires.clone(),
+            _claims: PhantomData,
+        }
+    }
+}
+
+impl<S, T> Service<Request<Body>> for AuthorizeService<S, T>
+where
+    T: Authority + Send + Sync + 'static,
+    S: Service<Request<Body>, Response = Response> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        if let Err(err_res) = authorize(req.extensions().get::<T>(), &self.requires) {
+            return Box::pin(async move { Ok(err_res.into_response()) });
+        }
+
+        let future = self.inner.call(req);
+        Box::pin(future)
+    }
+}
+
+/// 权限策略 配合 `Authorized` 提取器使用
+///
+/// # Examples
+/// ```ignore
+/// struct Admin;
+///
+/// impl Policy for Admin {
+///     fn requires() -> Vec<Require> {
+///         vec![Require::any_role(["admin"])]
+///     }
+/// }
+///
+/// async fn delete_user(Authorized(claims, ..): Authorized<Claims, Admin>) {}
+/// ```
+pub trait Policy {
+    fn requires() -> Vec<Require>;
+}
+
+/// 提取满足策略 `P` 的 claims 需在 `JwtAuth` 之后使用
+#[must_use]
+pub struct Authorized<T, P>(pub T, pub PhantomData<P>);
+
+#[async_trait]
+impl<T, P, S> FromRequestParts<S> for Authorized<T, P>
+where
+    T: Authority + Clone + Send + Sync + 'static,
+    P: Policy,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _: &S) -> Result<Self, Self::Rejection> {
+        let claims = parts.extensions.get::<T>();
+        authorize(claims, &P::requires()).map_err(|err| err.into_response())?;
+        Ok(Self(claims.cloned().unwrap(), PhantomData))
+    }
+}
+
+#[test]
+fn require_check() {
+    struct Claims;
+
+    impl Authority for Claims {
+        fn roles(&self) -> Vec<&str> {
+            vec!["editor", "viewer"]
+        }
+
+        fn scopes(&self) -> Vec<&str> {
+            vec!["article:read"]
+        }
+    }
+
+    assert!(Require::any_role(["admin", "editor"]).check(&Claims));
+    assert!(!Require::all_roles(["admin", "editor"]).check(&Claims));
+    assert!(Require::all_scopes(["article:read"]).check(&Claims));
+    assert!(!Require::any_scope(["article:write"]).check(&Claims));
+
+    let requires = [
+        Require::any_role(["editor"]),
+        Require::any_scope(["article:write"]),
+    ];
+    let res = authorize(Some(&Claims), &requires)
+        .unwrap_err()
+        .into_response();
+    assert_eq!(res.status(), 403);
+    let res = authorize::<Claims>(None, &requires)
+        .unwrap_err()
+        .into_response();
+    assert_eq!(res.status(), 401);
+}
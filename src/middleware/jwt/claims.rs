@@ -0,0 +1,296 @@
+use jsonwebtoken::{Algorithm, Validation};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use uuid::Uuid;
+
+use super::{timestamp, JwtRejection};
+
+/// token 校验规则
+///
+/// `exp` 始终必需 配置 `issuer` / `audience` 后对应的 claim 也变为必需
+///
+/// # Examples
+/// ```
+/// use mll_axum_utils::middleware::jwt::JwtValidation;
+/// JwtValidation::new()
+///     .issuer(["auth.example.com"])
+///     .audience(["order-service"])
+///     .leeway(30)
+///     .nbf(true)
+///     .require(["sub", "jti"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct JwtValidation {
+    leeway: u64,
+    nbf: bool,
+    issuer: Vec<String>,
+    audience: Vec<String>,
+    required: Vec<String>,
+}
+
+impl Default for JwtValidation {
+    fn default() -> Self {
+        Self {
+            leeway: 60,
+            nbf: false,
+            issuer: vec![],
+            audience: vec![],
+            required: vec![],
+        }
+    }
+}
+
+impl JwtValidation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 校验 `exp` `nbf` 时允许的时钟误差 默认 60 单位 s
+    pub fn leeway(mut self, leeway: u64) -> Self {
+        self.leeway = leeway;
+        self
+    }
+
+    /// 是否校验 `nbf` 默认不校验
+    pub fn nbf(mut self, nbf: bool) -> Self {
+        self.nbf = nbf;
+        self
+    }
+
+    /// 允许的签发者 token 的 `iss` 需为其中之一
+    pub fn issuer<I: IntoIterator<Item = S>, S: Into<String>>(mut self, issuer: I) -> Self {
+        self.issuer = issuer.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 允许的受众 token 的 `aud` 需包含其中之一
+    pub fn audience<I: IntoIterator<Item = S>, S: Into<String>>(mut self, audience: I) -> Self {
+        self.audience = audience.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// 必需存在的 claim 如 `sub` `nbf` `jti`
+    pub fn require<I: IntoIterator<Item = S>, S: Into<String>>(mut self, claims: I) -> Self {
+        self.required.extend(claims.into_iter().map(Into::into));
+        self
+    }
+
+    /// 转换为 jsonwebtoken 的校验规则
+    pub(crate) fn validation(&self, algorithm: Algorithm) -> Validation {
+        let mut validation = Validation::new(algorithm);
+        validation.leeway = self.leeway;
+        validation.validate_nbf = self.nbf;
+        if !self.issuer.is_empty() {
+            validation.set_issuer(&self.issuer);
+            validation.required_spec_claims.insert("iss".into());
+        }
+        if !self.audience.is_empty() {
+            validation.set_audience(&self.audience);
+            validation.required_spec_claims.insert("aud".into());
+        }
+        validation
+            .required_spec_claims
+            .extend(self.required.iter().cloned());
+        validation
+    }
+
+    /// 检查必需的 claim 后解析为 claims 类型
+    pub(crate) fn parse<T: DeserializeOwned>(&self, claims: Value) -> Result<T, JwtRejection> {
+        let missing = self
+            .required
+            .iter()
+            .any(|name| claims.get(name.as_str()).is_none_or(Value::is_null));
+        if missing {
+            return Err(JwtRejection::InvalidClaims);
+        }
+
+        serde_json::from_value(claims).map_err(|_| JwtRejection::InvalidClaims)
+    }
+}
+
+/// 标准 claims 可通过 `#[serde(flatten)]` 嵌入自定义 claims
+///
+/// # Examples
+/// ```
+/// use mll_axum_utils::middleware::jwt::{JwtToken, JwtValidation, StandardClaims};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// struct Claims {
+///     exp: u64,
+///     #[serde(flatten)]
+///     std: StandardClaims,
+///     name: String,
+/// }
+///
+/// impl JwtToken for Claims {
+///     fn validation() -> JwtValidation {
+///         JwtValidation::new().issuer(["auth"]).audience(["api"])
+///     }
+///
+///     fn jti(&self) -> Option<String> {
+///         self.std.jti.clone()
+///     }
+///
+///     fn sub(&self) -> Option<String> {
+///         self.std.sub.clone()
+///     }
+///
+///     fn iat(&self) -> Option<u64> {
+///         self.std.iat
+///     }
+/// }
+///
+/// let claims = Claims {
+///     exp: Claims::expiration(),
+///     std: StandardClaims::new().subject("1").issuer("auth").audience(["api"]),
+///     name: "mll".into(),
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StandardClaims {
+    /// 主体
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    /// 签发时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat: Option<u64>,
+    /// 生效时间
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<u64>,
+    /// token id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jti: Option<String>,
+    /// 签发者
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// 受众 单个时序列化为字符串
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "one_or_many",
+        deserialize_with = "string_or_vec"
+    )]
+    pub aud: Vec<String>,
+}
+
+impl StandardClaims {
+    /// 以当前时间为签发时间 并生成随机 jti
+    pub fn new() -> Self {
+        Self {
+            iat: Some(timestamp()),
+            jti: Some(Uuid::new_v4().to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn subject<S: Into<String>>(mut self, sub: S) -> Self {
+        self.sub = Some(sub.into());
+        self
+    }
+
+    pub fn not_before(mut self, nbf: u64) -> Self {
+        self.nbf = Some(nbf);
+        self
+    }
+
+    pub fn issuer<S: Into<String>>(mut self, iss: S) -> Self {
+        self.iss = Some(iss.into());
+        self
+    }
+
+    pub fn audience<I: IntoIterator<Item = S>, S: Into<String>>(mut self, aud: I) -> Self {
+        self.aud = aud.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+fn one_or_many<S: Serializer>(aud: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+    match aud {
+        [aud] => aud.serialize(serializer),
+        aud => aud.serialize(serializer),
+    }
+}
+
+fn string_or_vec<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Aud {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match Aud::deserialize(deserializer)? {
+        Aud::One(aud) => vec![aud],
+        Aud::Many(aud) => aud,
+    })
+}
+
+#[test]
+fn claim_validation() {
+    use super::JwtToken;
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    struct Claims {
+        exp: u64,
+        #[serde(flatten)]
+        std: StandardClaims,
+    }
+
+    impl JwtToken for Claims {}
+
+    let validation = JwtValidation::new()
+        .issuer(["auth"])
+        .audience(["api"])
+        .leeway(0)
+        .nbf(true)
+        .require(["sub"]);
+    let std = StandardClaims::new()
+        .subject("1")
+        .issuer("auth")
+        .audience(["api"]);
+    let verify = |exp: u64, std: StandardClaims| {
+        let token = Claims { exp, std }.encode().unwrap();
+        Claims::default().verify(&token, &Claims::keys(), &validation)
+    };
+
+    let exp = Claims::expiration();
+    let claims = verify(exp, std.clone()).unwrap();
+    assert_eq!(claims.std, std);
+    let check = |exp: u64, std: StandardClaims| verify(exp, std).unwrap_err();
+    assert_eq!(check(timestamp() - 10, std.clone()), JwtRejection::Expired);
+    let iss = std.clone().issuer("other");
+    assert_eq!(check(exp, iss), JwtRejection::WrongIssuer);
+    let aud = std.clone().audience(["other"]);
+    assert_eq!(check(exp, aud), JwtRejection::WrongAudience);
+    let nbf = std.clone().not_before(timestamp() + 60);
+    assert_eq!(check(exp, nbf), JwtRejection::NotYetValid);
+    let no_iss = StandardClaims {
+        iss: None,
+        ..std.clone()
+    };
+    assert_eq!(check(exp, no_iss), JwtRejection::InvalidClaims);
+    let no_sub = StandardClaims {
+        sub: None,
+        ..std.clone()
+    };
+    assert_eq!(check(exp, no_sub), JwtRejection::InvalidClaims);
+
+    // 默认允许 60s 误差 且不校验 nbf iss aud
+    let token = Claims {
+        exp: timestamp() - 10,
+        std: StandardClaims::new().not_before(timestamp() + 600),
+    };
+    let token = token.encode().unwrap();
+    assert!(Claims::default()
+        .verify(&token, &Claims::keys(), &JwtValidation::default())
+        .is_ok());
+
+    // 单个受众序列化为字符串
+    let claims = serde_json::to_value(StandardClaims::default().audience(["api"])).unwrap();
+    assert_eq!(claims, serde_json::json!({ "aud": "api" }));
+    let config = r#"{"issuer":["auth"],"leeway":0}"#;
+    let config: JwtValidation = serde_json::from_str(config).unwrap();
+    assert_eq!(config, JwtValidation::new().issuer(["auth"]).leeway(0));
+}
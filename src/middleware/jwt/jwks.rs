@@ -0,0 +1,179 @@
+use axum::{http::header::CACHE_CONTROL, response::IntoResponse, routing::get, Json, Router};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::jwk::{
+    AlgorithmParameters, CommonParameters, EllipticCurve, EllipticCurveKeyParameters,
+    EllipticCurveKeyType, Jwk, JwkSet, OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse,
+    RSAKeyParameters, RSAKeyType,
+};
+use pem::Pem;
+use simple_asn1::{oid, ASN1Block};
+
+use super::{JwtKey, JwtKeys, JwtToken};
+
+impl JwtKey {
+    /// 转换为 JWK HMAC 密钥和未加载公钥的密钥返回 None
+    pub fn to_jwk(&self) -> Option<Jwk> {
+        let algorithm = public_params(self.public_key()?)?;
+        Some(Jwk {
+            common: CommonParameters {
+                public_key_use: Some(PublicKeyUse::Signature),
+                algorithm: Some(self.algorithm()),
+                key_id: self.kid().map(String::from),
+                ..Default::default()
+            },
+            algorithm,
+        })
+    }
+}
+
+/// 读取公钥的 JWK 参数 支持 `PUBLIC KEY` (SubjectPublicKeyInfo) 与 `RSA PUBLIC KEY` (PKCS#1)
+pub(super) fn public_params(public: &Pem) -> Option<AlgorithmParameters> {
+    let blocks = simple_asn1::from_der(&public.contents).ok()?;
+    if public.tag == "RSA PUBLIC KEY" {
+        return rsa_params(&blocks);
+    }
+
+    let [ASN1Block::Sequence(_, spki)] = blocks.as_slice() else {
+        return None;
+    };
+    let [ASN1Block::Sequence(_, algorithm), ASN1Block::BitString(_, _, key)] = spki.as_slice()
+    else {
+        return None;
+    };
+    let ASN1Block::ObjectIdentifier(_, id) = algorithm.first()? else {
+        return None;
+    };
+
+    if *id == oid!(1, 2, 840, 113_549, 1, 1, 1) {
+        rsa_params(&simple_asn1::from_der(key).ok()?)
+    } else if *id == oid!(1, 2, 840, 10_045, 2, 1) {
+        let curve = match algorithm.get(1)? {
+            ASN1Block::ObjectIdentifier(_, id) if *id == oid!(1, 2, 840, 10_045, 3, 1, 7) => {
+                EllipticCurve::P256
+            }
+            ASN1Block::ObjectIdentifier(_, id) if *id == oid!(1, 3, 132, 0, 34) => {
+                EllipticCurve::P384
+            }
+            _ => return None,
+        };
+        // 未压缩的点: 0x04 || x || y
+        let (x, y) = key.get(1..)?.split_at((key.len() - 1) / 2);
+        Some(AlgorithmParameters::EllipticCurve(
+            EllipticCurveKeyParameters {
+                key_type: EllipticCurveKeyType::EC,
+                curve,
+                x: URL_SAFE_NO_PAD.encode(x),
+                y: URL_SAFE_NO_PAD.encode(y),
+            },
+        ))
+    } else if *id == oid!(1, 3, 101, 112) {
+        Some(AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+            key_type: OctetKeyPairType::OctetKeyPair,
+            curve: EllipticCurve::Ed25519,
+            x: URL_SAFE_NO_PAD.encode(key),
+        }))
+    } else {
+        None
+    }
+}
+
+/// PKCS#1 RSA 公钥
+fn rsa_params(blocks: &[ASN1Block]) -> Option<AlgorithmParameters> {
+    let [ASN1Block::Sequence(_, key)] = blocks else {
+        return None;
+    };
+    let [ASN1Block::Integer(_, n), ASN1Block::Integer(_, e)] = key.as_slice() else {
+        return None;
+    };
+    Some(AlgorithmParameters::RSA(RSAKeyParameters {
+        key_type: RSAKeyType::RSA,
+        n: URL_SAFE_NO_PAD.encode(n.to_bytes_be().1),
+        e: URL_SAFE_NO_PAD.encode(e.to_bytes_be().1),
+    }))
+}
+
+impl JwtKeys {
+    /// 所有未退役密钥的公钥集合
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self.verifying().filter_map(JwtKey::to_jwk).collect(),
+        }
+    }
+}
+
+/// 公钥集合路由 `GET /.well-known/jwks.json`
+///
+/// 每次请求读取 `T::keys()` 密钥轮换后立即生效
+///
+/// # Examples
+/// ```ignore
+/// let app = Router::new()
+///     .route("/login", post(login))
+///     .merge(jwks_router::<Claims, _>());
+/// ```
+pub fn jwks_router<T, S>() -> Router<S>
+where
+    T: JwtToken,
+    S: Clone + Send + Sync + 'static,
+{
+    let handler = || async {
+        let jwks = T::keys().jwks();
+        ([(CACHE_CONTROL, "public, max-age=300")], Json(jwks)).into_response()
+    };
+    Router::new().route("/.well-known/jwks.json", get(handler))
+}
+
+#[test]
+fn jwks_endpoint() {
+    use axum::{body::Body, http::Request};
+    use jsonwebtoken::Algorithm;
+    use serde::{Deserialize, Serialize};
+    use tower::ServiceExt;
+
+    use super::{
+        key::{ED25519_PRIVATE, ED25519_PUBLIC},
+        timestamp,
+    };
+
+    #[derive(Serialize, Deserialize)]
+    struct Claims {
+        exp: u64,
+    }
+
+    impl JwtToken for Claims {}
+
+    let (private, public) = (ED25519_PRIVATE.as_bytes(), ED25519_PUBLIC.as_bytes());
+    let key = JwtKey::from_keys(Algorithm::EdDSA, private, public).unwrap();
+    let retired = JwtKey::from_public(Algorithm::EdDSA, public).unwrap();
+    // HMAC 密钥与已退役的密钥不公开
+    Claims::set_keys(
+        JwtKeys::new(key.clone().with_kid("a"))
+            .verify_with(JwtKey::from_secret("secret").with_kid("hs"), None)
+            .verify_with(retired.with_kid("old"), Some(timestamp() - 1)),
+    );
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let app = jwks_router::<Claims, ()>();
+        let jwks = |app: Router| async move {
+            let req = Request::get("/.well-known/jwks.json");
+            let res = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+            assert_eq!(res.headers()[CACHE_CONTROL], "public, max-age=300");
+            let mut body = res.into_body();
+            let body = axum::body::HttpBody::data(&mut body)
+                .await
+                .unwrap()
+                .unwrap();
+            let jwks: JwkSet = serde_json::from_slice(&body).unwrap();
+            let kids = jwks.keys.into_iter().map(|jwk| jwk.common.key_id.unwrap());
+            kids.collect::<Vec<_>>()
+        };
+        assert_eq!(jwks(app.clone()).await, ["a"]);
+
+        // 轮换后立即生效
+        Claims::set_keys(JwtKeys::new(key.clone().with_kid("a")).rotate(key.with_kid("b"), 60));
+        assert_eq!(jwks(app).await, ["b", "a"]);
+    });
+}
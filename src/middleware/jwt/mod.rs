@@ -0,0 +1,757 @@
+use std::{
+    marker::PhantomData,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::{
+    async_trait,
+    body::Body,
+    extract::FromRequestParts,
+    http::{request::Parts, HeaderMap, Request, Uri},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use jsonwebtoken::{errors::ErrorKind, Header};
+use serde::{Deserialize, Serialize};
+use tower::{Layer, Service};
+
+use super::filter::{FilterMode, PathFilter};
+use crate::{log::Log, res::Res, utils::timestamp};
+
+mod authz;
+mod claims;
+mod jwks;
+mod key;
+mod refresh;
+mod rejection;
+mod renew;
+mod revoke;
+mod source;
+mod tenant;
+
+pub use authz::*;
+pub use claims::*;
+pub use jwks::*;
+pub use key::*;
+pub use refresh::*;
+pub use rejection::*;
+pub use renew::*;
+pub use revoke::*;
+pub use source::*;
+pub use tenant::*;
+
+/// 验证 toekn 并解析 token 携带的数据
+///
+/// 经过 `JwtAuth` 时只使用其验证结果 不再重新解码 token 可选认证时使用 `Option<Jwt<T>>`
+///
+/// 只读取请求头与 uri 可与 `Json` `VJson` 等读取请求体的提取器同时使用
+/// # Examples
+/// ```ignore
+/// async fn create(Jwt(claims): Jwt<Claims>, VJson(article): VJson<Article>) {}
+/// ```
+#[must_use]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Jwt<T: JwtToken + Default>(pub T);
+
+#[async_trait]
+impl<T, S> FromRequestParts<S> for Jwt<T>
+where
+    T: JwtToken + Default + Clone + Send + Sync,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        // 已经过 JwtAuth 验证
+        if let Some(claims) = parts.extensions.get::<T>() {
+            return Ok(Jwt(claims.clone()));
+        }
+        // 经过 JwtAuth 但未通过验证或路由跳过了验证 不能使用 `T::keys()` 重新解码
+        if let Some(rejected) = parts.extensions.get::<Rejected<T>>() {
+            return Err(rejected.rejection.into_response());
+        }
+
+        // 未配置 JwtAuth
+        let validation = T::validation();
+        let claims = auth_token::<T>(
+            &parts.headers,
+            &parts.uri,
+            &T::keys(),
+            &validation,
+            T::TOKEN_SOURCES,
+        )
+        .map_err(IntoResponse::into_response)?;
+        let revocation = parts.extensions.get::<JwtRevocation>().cloned();
+        if let Some(revocation) = revocation {
+            check_revoked(&revocation, &claims).await?;
+        }
+        Ok(Jwt(claims))
+    }
+}
+
+/// `JwtAuth` 未放入 claims 的原因 匿名放行或跳过验证时放入请求扩展
+struct Rejected<T> {
+    rejection: JwtRejection,
+    _claims: PhantomData<fn() -> T>,
+}
+
+impl<T> Rejected<T> {
+    fn new(rejection: JwtRejection) -> Self {
+        Self {
+            rejection,
+            _claims: PhantomData,
+        }
+    }
+}
+
+fn auth_token<T: JwtToken + Default>(
+    headers: &HeaderMap,
+    uri: &Uri,
+    keys: &JwtKeys,
+    validation: &JwtValidation,
+    sources: &[TokenSource],
+) -> Result<T, JwtRejection> {
+    let token = extract_token(sources, headers, uri).ok_or(JwtRejection::Missing)?;
+    T::default().verify(&token, keys, validation)
+}
+
+async fn check_revoked<T: JwtToken>(
+    revocation: &JwtRevocation,
+    claims: &T,
+) -> Result<(), Response> {
+    match revocation.is_revoked(claims).await {
+        Ok(true) => Err(JwtRejection::Revoked.into_response()),
+        Ok(false) => Ok(()),
+        Err(err_res) => Err(err_res.into_response()),
+    }
+}
+
+/// # Examples
+/// ```no_run
+/// use std::net::SocketAddr;
+/// use axum::{Router,Json,Extension};
+/// use axum::routing::{get, post};
+/// use mll_axum_utils::middleware::jwt::{JwtAuth,JwtToken};
+/// use mll_axum_utils::middleware::logger::Logger;
+/// use mll_axum_utils::{utils,res::Res};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[tokio::main]
+/// async fn main() {
+/// let addr = "127.0.0.1:3000";
+///     let app = Router::new()
+///         .route("/index", get(index))
+///         .route("/login", post(login))
+///         .layer(JwtAuth::<Claims>::new(vec!["/login"]))
+///         .layer(Logger::default());
+///
+///     axum::Server::bind(&addr.parse().unwrap())
+///         .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+///         .await
+///         .unwrap();
+/// }
+///
+/// async fn login(Json(user): Json<User>) -> utils::Result<String> {
+/// let token = Claims::new(user).encode()?;
+///     // some validation
+///     Ok(Res::ok(token))
+/// }
+///
+/// async fn index(Extension(token): Extension<Claims>) -> &'static str {
+///     println!("{:?}", token);
+///     "身份认证成功 允许访问"
+/// }
+///
+/// #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// struct User {
+///     uid: u64,
+///     name: String,
+/// }
+///
+/// #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+/// struct Claims {
+///     exp: u64,
+///     user: User,
+/// }
+///
+/// impl JwtToken for Claims {}
+/// impl Claims {
+///     fn new(user: User) -> Self {
+///         Self {
+///             exp: Self::expiration(),
+///             user,
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct JwtAuth<T> {
+    filter: Arc<PathFilter>,
+    mode: FilterMode,
+    optional: bool,
+    claims: Arc<T>,
+    keys: Option<Arc<JwtKeys>>,
+    revocation: Option<JwtRevocation>,
+    sources: Option<Arc<Vec<TokenSource>>>,
+    validation: Option<Arc<JwtValidation>>,
+    renewal: Option<(Arc<JwtRenewal>, Reissue<T>)>,
+    tenants: Option<JwtTenants>,
+}
+
+impl<T> JwtAuth<T>
+where
+    T: Default + JwtToken,
+{
+    /// 按完全匹配跳过验证的路径
+    #[allow(dead_code)]
+    pub fn new(filter: Vec<&'static str>) -> Self {
+        Self::exempt(filter.into())
+    }
+
+    /// 匹配的路由跳过验证 其余路由需要验证
+    /// # Examples
+    /// ```ignore
+    /// let filter = PathFilter::from(vec!["/login", "GET /articles/*", "/share/:id"])
+    ///     .rule(Rule::prefix("/public"));
+    /// JwtAuth::<Claims>::exempt(filter);
+    /// ```
+    pub fn exempt(filter: PathFilter) -> Self {
+        Self::with_filter(filter, FilterMode::Exempt)
+    }
+
+    /// 仅匹配的路由需要验证 其余路由直接放行
+    /// # Examples
+    /// ```ignore
+    /// JwtAuth::<Claims>::protect(PathFilter::new().rule(Rule::prefix("/admin")));
+    /// ```
+    pub fn protect(filter: PathFilter) -> Self {
+        Self::with_filter(filter, FilterMode::Protect)
+    }
+
+    fn with_filter(filter: PathFilter, mode: FilterMode) -> Self {
+        Self {
+            filter: Arc::new(filter),
+            mode,
+            optional: false,
+            claims: Arc::new(T::default()),
+            keys: None,
+            revocation: None,
+            sources: None,
+            validation: None,
+            renewal: None,
+            tenants: None,
+        }
+    }
+
+    /// 可选认证 token 有效时放入 claims 未携带或无效时按匿名请求放行
+    ///
+    /// handler 中使用 `Option<Jwt<T>>` 或 `Option<Extension<T>>` 读取 claims
+    /// # Examples
+    /// ```ignore
+    /// let app = Router::new()
+    ///     .route("/feed", get(feed))
+    ///     .layer(JwtAuth::<Claims>::new(vec![]).optional(true));
+    ///
+    /// async fn feed(claims: Option<Jwt<Claims>>) -> String {
+    ///     match claims {
+    ///         Some(Jwt(claims)) => format!("{} 的推荐", claims.user.name),
+    ///         None => "热门推荐".into(),
+    ///     }
+    /// }
+    /// ```
+    pub fn optional(mut self, optional: bool) -> Self {
+        self.optional = optional;
+        self
+    }
+
+    /// 指定该层使用的密钥 未指定时使用 `T::keys()`
+    ///
+    /// 只负责验证 token 的服务可以只加载公钥
+    /// ```ignore
+    /// JwtAuth::<Claims>::new(vec![]).key(JwtKey::from_public_file(Algorithm::RS256, "public.pem")?)
+    /// ```
+    pub fn key(self, key: JwtKey) -> Self {
+        self.keys(key.into())
+    }
+
+    /// 指定该层使用的密钥集合 未指定时使用 `T::keys()`
+    pub fn keys(mut self, keys: JwtKeys) -> Self {
+        self.keys = Some(Arc::new(keys));
+        self
+    }
+
+    /// 多租户 按请求的租户标识选择密钥集合 设置后忽略 `Self::keys`
+    ///
+    /// 验证通过后将 `TenantId` 放入请求扩展
+    /// # Examples
+    /// ```ignore
+    /// let tenants = JwtTenants::new(TenantSource::header("X-Tenant"))
+    ///     .tenant("a", JwtTenant::new(JwtKey::from_env("TENANT_A_SECRET")?));
+    /// JwtAuth::<Claims>::new(vec!["/login"]).tenants(tenants);
+    /// ```
+    pub fn tenants(mut self, tenants: JwtTenants) -> Self {
+        self.tenants = Some(tenants);
+        self
+    }
+
+    /// 按顺序指定 token 的读取位置 未指定时使用 `T::TOKEN_SOURCES`
+    /// # Examples
+    /// ```ignore
+    /// JwtAuth::<Claims>::new(vec!["/login"]).sources(vec![
+    ///     TokenSource::Bearer,
+    ///     TokenSource::cookie("access_token"),
+    ///     TokenSource::query("token"),
+    /// ]);
+    /// ```
+    pub fn sources(mut self, sources: Vec<TokenSource>) -> Self {
+        self.sources = Some(Arc::new(sources));
+        self
+    }
+
+    /// 指定该层的校验规则 未指定时使用 `T::validation()`
+    /// # Examples
+    /// ```ignore
+    /// JwtAuth::<Claims>::new(vec!["/login"])
+    ///     .validation(JwtValidation::new().issuer(["auth"]).audience(["order-service"]));
+    /// ```
+    pub fn validation(mut self, validation: JwtValidation) -> Self {
+        self.validation = Some(Arc::new(validation));
+        self
+    }
+
+    /// 检查 token 是否已被吊销 同时将 `JwtRevocation` 放入请求扩展
+    pub fn revocation(mut self, revocation: JwtRevocation) -> Self {
+        self.revocation = Some(revocation);
+        self
+    }
+
+    /// 滑动续期 token 临近过期时通过 `JwtRefresh::renew` 更新 claims 并随响应返回新 token
+    pub fn renewal(mut self, renewal: JwtRenewal) -> Self
+    where
+        T: JwtRefresh,
+    {
+        self.renewal = Some((Arc::new(renewal), reissue::<T>));
+        self
+    }
+}
+
+impl<S, T> Layer<S> for JwtAuth<T>
+where
+    T: Default + JwtToken,
+{
+    type Service = JwtAuthService<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        // 吊销记录需保留到租户 token 全部过期
+        if let (Some(revocation), Some(tenants)) = (&self.revocation, &self.tenants) {
+            revocation.extend(tenants.max_lifetime::<T>());
+        }
+        JwtAuthService {
+            inner,
+            filter: self.filter.clone(),
+            mode: self.mode,
+            optional: self.optional,
+            claims: self.claims.clone(),
+            keys: self.keys.clone(),
+            revocation: self.revocation.clone(),
+            sources: self.sources.clone(),
+            validation: self.validation.clone(),
+            renewal: self.renewal.clone(),
+            tenants: self.tenants.clone(),
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Clone)]
+pub struct JwtAuthService<S, T> {
+    inner: S,
+    filter: Arc<PathFilter>,
+    mode: FilterMode,
+    optional: bool,
+    claims: Arc<T>,
+    keys: Option<Arc<JwtKeys>>,
+    revocation: Option<JwtRevocation>,
+    sources: Option<Arc<Vec<TokenSource>>>,
+    validation: Option<Arc<JwtValidation>>,
+    renewal: Option<(Arc<JwtRenewal>, Reissue<T>)>,
+    tenants: Option<JwtTenants>,
+}
+
+impl<S, T> Service<Request<Body>> for JwtAuthService<S, T>
+where
+    T: JwtToken + Default + Sync + Send + 'static,
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let revocation = self.revocation.clone();
+        if let Some(revocation) = &revocation {
+            req.extensions_mut().insert(revocation.clone());
+        }
+
+        if !self.mode.applies(&self.filter, &req) {
+            req.extensions_mut()
+                .insert(Rejected::<T>::new(JwtRejection::Missing));
+            let future = self.inner.call(req);
+            return Box::pin(future);
+        }
+
+        let sources = self
+            .sources
+            .as_deref()
+            .map_or(T::TOKEN_SOURCES, Vec::as_slice);
+        let validation = self
+            .validation
+            .clone()
+            .unwrap_or_else(|| Arc::new(T::validation()));
+        let claims = self.resolve_keys(&req, sources).and_then(|(keys, tenant)| {
+            let claims = auth_token::<T>(req.headers(), req.uri(), &keys, &validation, sources)?;
+            if let (Some(tenants), Some((id, _))) = (&self.tenants, &tenant) {
+                tenants.check(id, &claims)?;
+            }
+            Ok((claims, keys, tenant))
+        });
+        let (claims, keys, tenant) = match claims {
+            Ok(claims) => claims,
+            Err(rejection) if self.optional => {
+                req.extensions_mut().insert(Rejected::<T>::new(rejection));
+                return Box::pin(self.inner.call(req));
+            }
+            Err(rejection) => return Box::pin(async move { Ok(rejection.into_response()) }),
+        };
+        let duration = tenant
+            .as_ref()
+            .map_or(T::DURATION, |(_, tenant)| tenant.lifetime::<T>());
+        let renewal = self.renewal.clone();
+        let optional = self.optional;
+
+        // 吊销检查为异步 使用已就绪的 inner 留下克隆的服务
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            if let Some(revocation) = revocation {
+                match check_revoked(&revocation, &claims).await {
+                    Ok(()) => {}
+                    // 无法确认未被吊销时同样按匿名请求处理
+                    Err(_) if optional => {
+                        req.extensions_mut()
+                            .insert(Rejected::<T>::new(JwtRejection::Revoked));
+                        return inner.call(req).await;
+                    }
+                    Err(err_res) => return Ok(err_res),
+                }
+            }
+
+            let renewed = renewal.filter(|(renewal, _)| renewal.needs_renewal(&claims));
+            let renewed =
+                renewed.and_then(
+                    |(renewal, reissue)| match reissue(&claims, &keys, duration) {
+                        Ok(token) => Some((renewal, token)),
+                        Err(err) => {
+                            Log::warn(format!("token 续期失败 {err:?}"));
+                            None
+                        }
+                    },
+                );
+
+            req.extensions_mut().insert(claims);
+            if let Some((id, _)) = tenant {
+                req.extensions_mut().insert(id);
+            }
+            let mut res = inner.call(req).await?;
+            if let Some((renewal, token)) = renewed {
+                renewal.apply(&mut res, &token, duration);
+            }
+            Ok(res)
+        })
+    }
+}
+
+/// 请求所属的租户
+type Tenant = (TenantId, Arc<JwtTenant>);
+
+impl<S, T: JwtToken> JwtAuthService<S, T> {
+    /// 当前请求使用的密钥集合 多租户时同时返回租户
+    fn resolve_keys(
+        &self,
+        req: &Request<Body>,
+        sources: &[TokenSource],
+    ) -> Result<(Arc<JwtKeys>, Option<Tenant>), JwtRejection> {
+        let Some(tenants) = &self.tenants else {
+            return Ok((self.keys.clone().unwrap_or_else(T::keys), None));
+        };
+
+        let token = extract_token(sources, req.headers(), req.uri());
+        let (id, tenant) = tenants.resolve(req.headers(), req.uri(), token.as_deref())?;
+        Ok((tenant.keys(), Some((id, tenant))))
+    }
+}
+
+pub trait JwtToken
+where
+    Self: Serialize + for<'a> Deserialize<'a> + 'static,
+{
+    /// token key
+    ///
+    /// 仅在未通过 `Self::set_key` / `Self::set_keys` 设置运行时密钥时使用
+    const SECRET: &'static str = "my_key";
+
+    /// token 持续时间 默认15天 单位 s
+    const DURATION: u64 = 60 * 60 * 24 * 15;
+
+    /// 写入 header `typ` 的 token 类型
+    ///
+    /// 类型为 `REFRESH_TOKEN_TYPE` 的 token 只能解码为 refresh token 不能作为 access token 使用
+    const TOKEN_TYPE: &'static str = "JWT";
+
+    /// 按顺序读取 token 的位置 默认只读取 `Authorization: Bearer`
+    const TOKEN_SOURCES: &'static [TokenSource] = &[TokenSource::Bearer];
+
+    /// 设置运行时密钥 设置后不再使用 `Self::SECRET`
+    /// # Examples
+    /// ```no_run
+    /// use mll_axum_utils::middleware::jwt::{JwtKey, JwtToken};
+    /// # #[derive(serde::Serialize, serde::Deserialize)]
+    /// # struct Claims { exp: u64 }
+    /// # impl JwtToken for Claims {}
+    /// Claims::set_key(JwtKey::from_env("JWT_SECRET").unwrap());
+    /// ```
+    fn set_key(key: JwtKey) {
+        Self::set_keys(key.into())
+    }
+
+    /// 设置运行时密钥集合 用于密钥轮换
+    fn set_keys(keys: JwtKeys) {
+        keys.register::<Self>()
+    }
+
+    /// token 校验规则 默认只校验 `exp`
+    /// # Examples
+    /// ```ignore
+    /// fn validation() -> JwtValidation {
+    ///     JwtValidation::new().issuer(["auth"]).audience(["order-service"]).require(["sub"])
+    /// }
+    /// ```
+    fn validation() -> JwtValidation {
+        JwtValidation::default()
+    }
+
+    /// 当前使用的密钥集合
+    fn keys() -> Arc<JwtKeys> {
+        JwtKeys::registered::<Self>(|| JwtKey::from_secret(Self::SECRET).into())
+    }
+
+    /// token 编码
+    fn encode(&self) -> Result<String, Res<()>> {
+        self.encode_with(&Self::keys())
+    }
+
+    /// 使用指定密钥集合的签名密钥编码 token
+    fn encode_with(&self, keys: &JwtKeys) -> Result<String, Res<()>> {
+        let key = keys.signing();
+        let encoding = key
+            .encoding_key()
+            .ok_or_else(|| Res::internal_error("jwt 密钥未加载私钥 无法签发 token"))?;
+
+        let mut header = Header::new(key.algorithm());
+        header.kid = key.kid().map(String::from);
+        header.typ = Some(Self::TOKEN_TYPE.into());
+        let res = jsonwebtoken::encode(&header, self, encoding);
+
+        res.map_err(|err| Res::error(err.to_string()))
+    }
+
+    /// token 解码
+    fn decode(&self, token: &str) -> Result<Self, Res<()>> {
+        self.decode_with(token, &Self::keys())
+    }
+
+    /// 使用指定密钥集合解码 token
+    fn decode_with(&self, token: &str, keys: &JwtKeys) -> Result<Self, Res<()>> {
+        self.decode_with_validation(token, keys, &Self::validation())
+    }
+
+    /// 使用指定密钥集合与校验规则解码 token
+    fn decode_with_validation(
+        &self,
+        token: &str,
+        keys: &JwtKeys,
+        validation: &JwtValidation,
+    ) -> Result<Self, Res<()>> {
+        self.verify(token, keys, validation).map_err(Into::into)
+    }
+
+    /// 使用指定密钥集合与校验规则解码 token 失败时返回具体原因
+    ///
+    /// header 携带 kid 时只使用对应密钥 否则依次尝试所有未退役的密钥
+    fn verify(
+        &self,
+        token: &str,
+        keys: &JwtKeys,
+        validation: &JwtValidation,
+    ) -> Result<Self, JwtRejection> {
+        let header = jsonwebtoken::decode_header(token).map_err(|_| JwtRejection::Malformed)?;
+        // refresh token 与 access token 互不通用
+        let refresh = header.typ.as_deref() == Some(REFRESH_TOKEN_TYPE);
+        if refresh != (Self::TOKEN_TYPE == REFRESH_TOKEN_TYPE) {
+            return Err(JwtRejection::WrongType);
+        }
+        let candidates: Vec<&JwtKey> = match &header.kid {
+            Some(kid) => keys.find(kid).into_iter().collect(),
+            None => keys.verifying().collect(),
+        };
+
+        let mut last_err = None;
+        for key in candidates {
+            let rules = validation.validation(key.algorithm());
+            match jsonwebtoken::decode::<serde_json::Value>(token, key.decoding_key(), &rules) {
+                Ok(res) => return validation.parse(res.claims),
+                // 签名或算法不匹配时继续尝试下一个密钥
+                Err(err) => match err.kind() {
+                    ErrorKind::InvalidSignature | ErrorKind::InvalidAlgorithm => {
+                        last_err = Some(err)
+                    }
+                    _ => return Err(err.into()),
+                },
+            }
+        }
+
+        // 没有可用的密钥时视为签名无效
+        Err(last_err.map_or(JwtRejection::BadSignature, Into::into))
+    }
+
+    /// token id 用于吊销单个 token
+    fn jti(&self) -> Option<String> {
+        None
+    }
+
+    /// token 主体 如用户 id 用于吊销主体的所有 token
+    fn sub(&self) -> Option<String> {
+        None
+    }
+
+    /// token 签发时间戳 吊销主体时用于区分吊销前后签发的 token
+    fn iat(&self) -> Option<u64> {
+        None
+    }
+
+    /// token 过期时间: 当前时间 + Self::DURATION
+    fn expiration() -> u64 {
+        timestamp() + Self::DURATION
+    }
+}
+
+#[test]
+fn runtime_key() {
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    struct Claims {
+        exp: u64,
+    }
+
+    impl JwtToken for Claims {}
+
+    let claims = Claims {
+        exp: Claims::expiration(),
+    };
+    let old = claims.encode().unwrap();
+    Claims::set_key(JwtKey::from_secret("runtime_key"));
+    let token = claims.encode().unwrap();
+
+    // 设置运行时密钥后不再使用 SECRET
+    let keys = JwtKeys::new(JwtKey::from_secret("runtime_key"));
+    assert!(Claims::default().decode_with(&token, &keys).is_ok());
+    assert!(Claims::default().decode(&token).is_ok());
+    let rejection = Claims::default().verify(&old, &Claims::keys(), &Claims::validation());
+    assert_eq!(rejection.unwrap_err(), JwtRejection::BadSignature);
+}
+
+#[test]
+fn jwt_extractor() {
+    use axum::{http::StatusCode, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    struct Claims {
+        exp: u64,
+        name: String,
+    }
+
+    impl JwtToken for Claims {}
+
+    let valid = Claims {
+        exp: Claims::expiration(),
+        name: "mll".into(),
+    };
+    let valid = valid.encode().unwrap();
+    let expired = Claims {
+        exp: timestamp() - 600,
+        name: "mll".into(),
+    };
+    let expired = expired.encode().unwrap();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let name = |Jwt(claims): Jwt<Claims>| async move { claims.name };
+        let optional = |claims: Option<Jwt<Claims>>| async move {
+            claims.map_or("anonymous".into(), |Jwt(claims)| claims.name)
+        };
+        let call = |app: Router, uri: &'static str, token: Option<&str>| {
+            let mut req = Request::get(uri);
+            if let Some(token) = token {
+                req = req.header("Authorization", format!("Bearer {token}"));
+            }
+            let req = req.body(Body::empty()).unwrap();
+            async move {
+                let res = app.oneshot(req).await.unwrap();
+                let status = res.status();
+                let mut body = res.into_body();
+                let body = axum::body::HttpBody::data(&mut body).await;
+                let body = body.map(|body| body.unwrap()).unwrap_or_default();
+                let code = serde_json::from_slice::<serde_json::Value>(&body)
+                    .map_or(0, |body| body["data"]["code"].as_u64().unwrap_or(0));
+                (status, code, String::from_utf8_lossy(&body).into_owned())
+            }
+        };
+
+        // 未配置 JwtAuth 时自行解码
+        let app = Router::new().route("/", get(name));
+        let (_, _, body) = call(app.clone(), "/", Some(&valid)).await;
+        assert_eq!(body, "mll");
+        let (status, code, _) = call(app, "/", None).await;
+        assert_eq!((status, code), (StatusCode::UNAUTHORIZED, 40101));
+
+        // 跳过验证的路由不重新解码
+        let app = Router::new()
+            .route("/", get(name))
+            .route("/open", get(name))
+            .layer(JwtAuth::<Claims>::new(vec!["/open"]));
+        let (_, _, body) = call(app.clone(), "/", Some(&valid)).await;
+        assert_eq!(body, "mll");
+        let (status, code, _) = call(app, "/open", Some(&valid)).await;
+        assert_eq!((status, code), (StatusCode::UNAUTHORIZED, 40101));
+
+        // 可选认证 保留验证失败的原因
+        let app = Router::new()
+            .route("/", get(optional))
+            .route("/required", get(name))
+            .layer(JwtAuth::<Claims>::new(vec![]).optional(true));
+        let (_, _, body) = call(app.clone(), "/", Some(&valid)).await;
+        assert_eq!(body, "mll");
+        let (_, _, body) = call(app.clone(), "/", Some(&expired)).await;
+        assert_eq!(body, "anonymous");
+        let (status, code, _) = call(app, "/required", Some(&expired)).await;
+        assert_eq!((status, code), (StatusCode::UNAUTHORIZED, 40104));
+    });
+}
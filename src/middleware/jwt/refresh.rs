@@ -0,0 +1,396 @@
+use std::{collections::HashMap, sync::Arc, sync::Mutex};
+
+use axum::{async_trait, routing::post, Router};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::{timestamp, JwtKeys, JwtRevocation, JwtToken};
+use crate::{log::Log, res::Res, utils, validator::VJsonOrForm};
+
+/// refresh token 的 header `typ`
+pub const REFRESH_TOKEN_TYPE: &str = "refresh+jwt";
+
+/// 支持 refresh token 的 claims
+///
+/// access token 有效期为 `Self::DURATION` 应设置得较短 refresh token 有效期为 `Self::REFRESH_DURATION`
+pub trait JwtRefresh: JwtToken + Default + Clone + Send + Sync {
+    /// refresh token 持续时间 默认30天 单位 s
+    const REFRESH_DURATION: u64 = 60 * 60 * 24 * 30;
+
+    /// 重新签发 access token 前更新 claims 通常为 `self.exp = exp`
+    ///
+    /// `exp` 为新 token 的过期时间 多租户时按租户的 token 有效期计算
+    fn renew(&mut self, exp: u64);
+}
+
+/// refresh token 携带的数据
+///
+/// 同一次登录签发的 refresh token 属于同一个 family 每次刷新生成新的 jti
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RefreshClaims<T> {
+    /// token id
+    pub jti: String,
+    /// token family id
+    pub fid: String,
+    pub exp: u64,
+    /// access token 的 claims
+    pub data: T,
+}
+
+impl<T: JwtRefresh> JwtToken for RefreshClaims<T> {
+    const DURATION: u64 = T::REFRESH_DURATION;
+
+    const TOKEN_TYPE: &'static str = REFRESH_TOKEN_TYPE;
+
+    fn set_keys(keys: JwtKeys) {
+        T::set_keys(keys)
+    }
+
+    /// 与 access token 使用同一密钥集合
+    fn keys() -> Arc<JwtKeys> {
+        T::keys()
+    }
+}
+
+/// access token 与 refresh token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub token_type: String,
+    /// access token 有效期 单位 s
+    pub expires_in: u64,
+}
+
+/// refresh token 轮换结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    /// 轮换成功
+    Rotated,
+    /// 使用了已被轮换的旧 token 整个 family 已被吊销
+    Reused,
+    /// family 不存在 已过期或已被吊销
+    Unknown,
+}
+
+/// refresh token 状态存储
+#[async_trait]
+pub trait RefreshStore: Send + Sync + 'static {
+    /// 记录新 family 当前有效的 jti
+    async fn issue(&self, fid: &str, jti: &str, exp: u64) -> Result<(), Res<()>>;
+
+    /// `jti` 为 family 当前有效的 token 时替换为 `next` 否则吊销整个 family
+    async fn rotate(&self, fid: &str, jti: &str, next: &str, exp: u64)
+        -> Result<Rotation, Res<()>>;
+
+    /// 吊销整个 family
+    async fn revoke(&self, fid: &str) -> Result<(), Res<()>>;
+}
+
+/// 内存 refresh token 存储 适用于单实例部署
+#[derive(Default)]
+pub struct MemoryRefreshStore {
+    families: Mutex<HashMap<String, Family>>,
+}
+
+struct Family {
+    jti: String,
+    exp: u64,
+    revoked: bool,
+}
+
+impl MemoryRefreshStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RefreshStore for MemoryRefreshStore {
+    async fn issue(&self, fid: &str, jti: &str, exp: u64) -> Result<(), Res<()>> {
+        let mut families = self.families.lock().unwrap_or_else(|err| err.into_inner());
+        let now = timestamp();
+        families.retain(|_, family| family.exp > now);
+
+        let family = Family {
+            jti: jti.to_string(),
+            exp,
+            revoked: false,
+        };
+        families.insert(fid.to_string(), family);
+        Ok(())
+    }
+
+    async fn rotate(
+        &self,
+        fid: &str,
+        jti: &str,
+        next: &str,
+        exp: u64,
+    ) -> Result<Rotation, Res<()>> {
+        let mut families = self.families.lock().unwrap_or_else(|err| err.into_inner());
+        let Some(family) = families.get_mut(fid) else {
+            return Ok(Rotation::Unknown);
+        };
+
+        if family.revoked || family.exp <= timestamp() {
+            return Ok(Rotation::Unknown);
+        }
+        if family.jti != jti {
+            family.revoked = true;
+            return Ok(Rotation::Reused);
+        }
+
+        family.jti = next.to_string();
+        family.exp = exp;
+        Ok(Rotation::Rotated)
+    }
+
+    async fn revoke(&self, fid: &str) -> Result<(), Res<()>> {
+        let mut families = self.families.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(family) = families.get_mut(fid) {
+            family.revoked = true;
+        }
+        Ok(())
+    }
+}
+
+/// 签发与轮换 token 对
+///
+/// # Examples
+/// ```ignore
+/// let refresher = JwtRefresher::<Claims>::new(MemoryRefreshStore::new());
+///
+/// let app = Router::new()
+///     .route("/login", post(login))
+///     .merge(refresher.clone().router("/token/refresh"))
+///     .layer(JwtAuth::<Claims>::new(vec!["/login", "/token/refresh"]))
+///     .layer(Extension(refresher));
+///
+/// async fn login(
+///     Extension(refresher): Extension<JwtRefresher<Claims>>,
+///     VJsonOrForm(user): VJsonOrForm<User>,
+/// ) -> utils::Result<TokenPair> {
+///     let pair = refresher.issue(Claims::new(user)).await?;
+///     Ok(Res::ok(pair))
+/// }
+/// ```
+pub struct JwtRefresher<T> {
+    store: Arc<dyn RefreshStore>,
+    keys: Option<Arc<JwtKeys>>,
+    revocation: Option<JwtRevocation>,
+    _claims: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for JwtRefresher<T> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            keys: self.keys.clone(),
+            revocation: self.revocation.clone(),
+            _claims: Default::default(),
+        }
+    }
+}
+
+impl<T: JwtRefresh> JwtRefresher<T> {
+    pub fn new<R: RefreshStore>(store: R) -> Self {
+        Self {
+            store: Arc::new(store),
+            keys: None,
+            revocation: None,
+            _claims: Default::default(),
+        }
+    }
+
+    /// 指定使用的密钥集合 未指定时使用 `T::keys()`
+    pub fn keys(mut self, keys: JwtKeys) -> Self {
+        self.keys = Some(Arc::new(keys));
+        self
+    }
+
+    /// 刷新前检查 claims 是否已被吊销 已吊销时吊销整个 family
+    pub fn revocation(mut self, revocation: JwtRevocation) -> Self {
+        revocation.extend(T::REFRESH_DURATION);
+        self.revocation = Some(revocation);
+        self
+    }
+
+    /// 登录成功后签发新的 token 对
+    pub async fn issue(&self, claims: T) -> Result<TokenPair, Res<()>> {
+        let fid = Uuid::new_v4().to_string();
+        let jti = Uuid::new_v4().to_string();
+        let exp = RefreshClaims::<T>::expiration();
+
+        self.store.issue(&fid, &jti, exp).await?;
+        self.pair(claims, fid, jti, exp)
+    }
+
+    /// 使用 refresh token 换取新的 token 对 旧 refresh token 随即失效
+    ///
+    /// 重复使用已轮换的 refresh token 或 claims 已被吊销时会吊销整个 family
+    pub async fn refresh(&self, refresh_token: &str) -> Result<TokenPair, Res<()>> {
+        let keys = self.current_keys();
+        let RefreshClaims { jti, fid, data, .. } =
+            RefreshClaims::<T>::default().decode_with(refresh_token, &keys)?;
+        if let Some(revocation) = &self.revocation {
+            if revocation.is_revoked(&data).await? {
+                self.store.revoke(&fid).await?;
+                return Err(Res::auth("refresh token 已失效"));
+            }
+        }
+
+        let next = Uuid::new_v4().to_string();
+        let exp = RefreshClaims::<T>::expiration();
+        match self.store.rotate(&fid, &jti, &next, exp).await? {
+            Rotation::Rotated => {
+                let mut claims = data;
+                claims.renew(T::expiration());
+                self.pair(claims, fid, next, exp)
+            }
+            Rotation::Reused => {
+                Log::warn(format!("refresh token 被重复使用 已吊销 family {fid}"));
+                Err(Res::auth("refresh token 已失效"))
+            }
+            Rotation::Unknown => Err(Res::auth("refresh token 已失效")),
+        }
+    }
+
+    /// 退出登录 吊销 refresh token 所属的 family
+    pub async fn revoke(&self, refresh_token: &str) -> Result<(), Res<()>> {
+        let claims =
+            RefreshClaims::<T>::default().decode_with(refresh_token, &self.current_keys())?;
+        self.store.revoke(&claims.fid).await
+    }
+
+    /// 刷新 token 路由 `POST path` 请求体为 json 或 form: `refresh_token`
+    pub fn router<S>(self, path: &str) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        let handler = move |VJsonOrForm(req): VJsonOrForm<RefreshRequest>| async move {
+            let pair = self.refresh(&req.refresh_token).await?;
+            utils::Result::Ok(Res::ok(pair))
+        };
+        Router::new().route(path, post(handler))
+    }
+
+    fn current_keys(&self) -> Arc<JwtKeys> {
+        self.keys.clone().unwrap_or_else(T::keys)
+    }
+
+    fn pair(&self, claims: T, fid: String, jti: String, exp: u64) -> Result<TokenPair, Res<()>> {
+        let keys = self.current_keys();
+        let access_token = claims.encode_with(&keys)?;
+        let refresh = RefreshClaims {
+            jti,
+            fid,
+            exp,
+            data: claims,
+        };
+
+        Ok(TokenPair {
+            access_token,
+            refresh_token: refresh.encode_with(&keys)?,
+            token_type: "Bearer".into(),
+            expires_in: T::DURATION,
+        })
+    }
+}
+
+/// 刷新 token 请求
+#[derive(Debug, Clone, Deserialize, Validate)]
+pub struct RefreshRequest {
+    #[validate(length(min = 1, message = "refresh_token 不能为空"))]
+    pub refresh_token: String,
+}
+
+#[test]
+fn refresh_token_type() {
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    struct Claims {
+        exp: u64,
+        #[serde(flatten)]
+        std: super::StandardClaims,
+    }
+
+    impl JwtToken for Claims {}
+    impl JwtRefresh for Claims {
+        fn renew(&mut self, exp: u64) {
+            self.exp = exp;
+        }
+    }
+
+    let claims = Claims {
+        exp: Claims::expiration(),
+        std: super::StandardClaims::new().subject("1"),
+    };
+    let refresh = RefreshClaims {
+        jti: "jti".into(),
+        fid: "fid".into(),
+        exp: RefreshClaims::<Claims>::expiration(),
+        data: claims.clone(),
+    };
+    let access_token = claims.encode().unwrap();
+    let refresh_token = refresh.encode().unwrap();
+
+    // refresh token 不能作为 access token 使用 反之亦然
+    let rejection =
+        Claims::default().verify(&refresh_token, &Claims::keys(), &Claims::validation());
+    assert_eq!(rejection.unwrap_err(), super::JwtRejection::WrongType);
+    let rejection = RefreshClaims::<Claims>::default().verify(
+        &access_token,
+        &Claims::keys(),
+        &Claims::validation(),
+    );
+    assert_eq!(rejection.unwrap_err(), super::JwtRejection::WrongType);
+    assert!(RefreshClaims::<Claims>::default()
+        .verify(&refresh_token, &Claims::keys(), &Claims::validation())
+        .is_ok());
+}
+
+#[test]
+fn revoked_subject_cannot_refresh() {
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    struct Claims {
+        exp: u64,
+        #[serde(flatten)]
+        std: super::StandardClaims,
+    }
+
+    impl JwtToken for Claims {
+        fn sub(&self) -> Option<String> {
+            self.std.sub.clone()
+        }
+
+        fn iat(&self) -> Option<u64> {
+            self.std.iat
+        }
+    }
+    impl JwtRefresh for Claims {
+        fn renew(&mut self, exp: u64) {
+            self.exp = exp;
+        }
+    }
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let revocation = JwtRevocation::new(super::MemoryRevocationStore::new());
+        let refresher =
+            JwtRefresher::<Claims>::new(MemoryRefreshStore::new()).revocation(revocation.clone());
+        let mut std = super::StandardClaims::new().subject("1");
+        std.iat = Some(timestamp() - 10);
+        let claims = Claims {
+            exp: Claims::expiration(),
+            std,
+        };
+
+        let pair = refresher.issue(claims).await.unwrap();
+        let pair = refresher.refresh(&pair.refresh_token).await.unwrap();
+        revocation.revoke_subject::<Claims>("1").await.unwrap();
+        assert!(refresher.refresh(&pair.refresh_token).await.is_err());
+    });
+}
@@ -0,0 +1,231 @@
+use std::{fmt::Display, sync::RwLock};
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+};
+use jsonwebtoken::errors::{Error, ErrorKind};
+use serde::Serialize;
+
+use crate::res::Res;
+
+type Messages = fn(JwtRejection) -> String;
+
+static MESSAGES: RwLock<Option<Messages>> = RwLock::new(None);
+
+/// token 验证失败的原因
+///
+/// 响应状态码均为 401 `data` 中携带稳定的业务码与原因 前端可据此区分处理
+/// ```json
+/// {"code":401,"msg":"token 已过期","data":{"code":40104,"reason":"token_expired"}}
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JwtRejection {
+    /// 请求未携带 token
+    Missing,
+    /// token 格式错误
+    Malformed,
+    /// 签名无效或密钥未知
+    BadSignature,
+    /// token 已过期
+    Expired,
+    /// token 尚未生效
+    NotYetValid,
+    /// token 已被吊销
+    Revoked,
+    /// 受众不匹配
+    WrongAudience,
+    /// 签发者不匹配
+    WrongIssuer,
+    /// 缺少必需的 claim 或 claims 无法解析
+    InvalidClaims,
+    /// 无法确定租户或租户不存在
+    UnknownTenant,
+    /// token 类型不匹配 如将 refresh token 作为 access token 使用
+    WrongType,
+}
+
+#[derive(Serialize)]
+struct Reason {
+    code: u16,
+    reason: &'static str,
+}
+
+impl JwtRejection {
+    /// 业务码
+    pub const fn code(&self) -> u16 {
+        match self {
+            JwtRejection::Missing => 40101,
+            JwtRejection::Malformed => 40102,
+            JwtRejection::BadSignature => 40103,
+            JwtRejection::Expired => 40104,
+            JwtRejection::NotYetValid => 40105,
+            JwtRejection::Revoked => 40106,
+            JwtRejection::WrongAudience => 40107,
+            JwtRejection::WrongIssuer => 40108,
+            JwtRejection::InvalidClaims => 40109,
+            JwtRejection::UnknownTenant => 40110,
+            JwtRejection::WrongType => 40111,
+        }
+    }
+
+    /// 原因标识
+    pub const fn reason(&self) -> &'static str {
+        match self {
+            JwtRejection::Missing => "token_missing",
+            JwtRejection::Malformed => "token_malformed",
+            JwtRejection::BadSignature => "bad_signature",
+            JwtRejection::Expired => "token_expired",
+            JwtRejection::NotYetValid => "token_not_yet_valid",
+            JwtRejection::Revoked => "token_revoked",
+            JwtRejection::WrongAudience => "wrong_audience",
+            JwtRejection::WrongIssuer => "wrong_issuer",
+            JwtRejection::InvalidClaims => "invalid_claims",
+            JwtRejection::UnknownTenant => "unknown_tenant",
+            JwtRejection::WrongType => "wrong_token_type",
+        }
+    }
+
+    /// 默认提示消息
+    pub const fn message(&self) -> &'static str {
+        match self {
+            JwtRejection::Missing => "请求未携带token",
+            JwtRejection::Malformed => "token 格式错误",
+            JwtRejection::BadSignature => "token 签名无效",
+            JwtRejection::Expired => "token 已过期",
+            JwtRejection::NotYetValid => "token 尚未生效",
+            JwtRejection::Revoked => "token 已被吊销",
+            JwtRejection::WrongAudience => "token 受众不匹配",
+            JwtRejection::WrongIssuer => "token 签发者不匹配",
+            JwtRejection::InvalidClaims => "token 数据无效",
+            JwtRejection::UnknownTenant => "token 租户无效",
+            JwtRejection::WrongType => "token 类型不匹配",
+        }
+    }
+
+    /// 替换响应中的提示消息
+    /// # Examples
+    /// ```
+    /// use mll_axum_utils::middleware::jwt::JwtRejection;
+    /// JwtRejection::localize(|rejection| match rejection {
+    ///     JwtRejection::Expired => "token expired".into(),
+    ///     _ => "unauthorized".into(),
+    /// });
+    /// ```
+    pub fn localize(messages: Messages) {
+        *MESSAGES.write().unwrap_or_else(|err| err.into_inner()) = Some(messages);
+    }
+}
+
+impl Display for JwtRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let messages = *MESSAGES.read().unwrap_or_else(|err| err.into_inner());
+        match messages {
+            Some(messages) => write!(f, "{}", messages(*self)),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl IntoResponse for JwtRejection {
+    fn into_response(self) -> Response {
+        let reason = Reason {
+            code: self.code(),
+            reason: self.reason(),
+        };
+        Res::new_data(StatusCode::UNAUTHORIZED, self, reason).into_response()
+    }
+}
+
+impl From<JwtRejection> for Res<()> {
+    fn from(rejection: JwtRejection) -> Self {
+        Res::auth(rejection)
+    }
+}
+
+impl From<Error> for JwtRejection {
+    fn from(err: Error) -> Self {
+        match err.kind() {
+            ErrorKind::InvalidSignature
+            | ErrorKind::InvalidAlgorithm
+            | ErrorKind::InvalidEcdsaKey
+            | ErrorKind::InvalidRsaKey(_)
+            | ErrorKind::InvalidKeyFormat => JwtRejection::BadSignature,
+            ErrorKind::ExpiredSignature => JwtRejection::Expired,
+            ErrorKind::ImmatureSignature => JwtRejection::NotYetValid,
+            ErrorKind::InvalidAudience => JwtRejection::WrongAudience,
+            ErrorKind::InvalidIssuer => JwtRejection::WrongIssuer,
+            ErrorKind::InvalidSubject | ErrorKind::MissingRequiredClaim(_) => {
+                JwtRejection::InvalidClaims
+            }
+            _ => JwtRejection::Malformed,
+        }
+    }
+}
+
+#[test]
+fn typed_rejections() {
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use serde::Deserialize;
+    use serde_json::{json, Value};
+    use tower::ServiceExt;
+
+    use super::{timestamp, JwtAuth, JwtKey, JwtToken};
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    struct Claims {
+        exp: u64,
+    }
+
+    impl JwtToken for Claims {}
+
+    let valid = Claims {
+        exp: Claims::expiration(),
+    };
+    let forged = valid.encode_with(&JwtKey::from_secret("other").into());
+    let expired = Claims {
+        exp: timestamp() - 600,
+    };
+    let cases = [
+        (None, JwtRejection::Missing),
+        (Some("abc".to_string()), JwtRejection::Malformed),
+        (Some(forged.unwrap()), JwtRejection::BadSignature),
+        (Some(expired.encode().unwrap()), JwtRejection::Expired),
+    ];
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let app = Router::new()
+            .route("/", get(|| async {}))
+            .layer(JwtAuth::<Claims>::new(vec![]));
+        for (token, rejection) in cases {
+            let mut req = Request::get("/");
+            if let Some(token) = token {
+                req = req.header("Authorization", format!("Bearer {token}"));
+            }
+            let res = app
+                .clone()
+                .oneshot(req.body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+            let mut body = res.into_body();
+            let body = axum::body::HttpBody::data(&mut body)
+                .await
+                .unwrap()
+                .unwrap();
+            let body: Value = serde_json::from_slice(&body).unwrap();
+            let data = json!({ "code": rejection.code(), "reason": rejection.reason() });
+            assert_eq!(body["msg"], rejection.message());
+            assert_eq!(body["data"], data);
+        }
+    });
+
+    // 转换为 Res 时同为 401
+    let res: Res<()> = JwtRejection::Revoked.into();
+    assert_eq!(res.into_response().status(), StatusCode::UNAUTHORIZED);
+    let err = Error::from(ErrorKind::InvalidAudience);
+    assert_eq!(JwtRejection::from(err), JwtRejection::WrongAudience);
+}
@@ -0,0 +1,191 @@
+use std::borrow::Cow;
+
+use axum::{
+    http::{header::SET_COOKIE, HeaderName, HeaderValue},
+    response::Response,
+};
+use serde::Serialize;
+
+use super::{timestamp, JwtKeys, JwtRefresh};
+use crate::{log::Log, res::Res};
+
+/// 重新签发 token 参数为 claims 密钥集合与 token 有效期
+pub(crate) type Reissue<T> = fn(&T, &JwtKeys, u64) -> Result<String, Res<()>>;
+
+/// 续期后的 token 返回位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenewTarget {
+    /// 响应头
+    Header(Cow<'static, str>),
+    /// `Set-Cookie`
+    Cookie(Cow<'static, str>),
+}
+
+/// 滑动续期
+///
+/// token 剩余有效期不足 `window` 秒时 `JwtAuth` 会签发新的 token 并随响应返回
+///
+/// # Examples
+/// ```ignore
+/// // 从 cookie 读取 token 剩余不足 1 天时写回同名 cookie
+/// JwtAuth::<Claims>::new(vec!["/login"])
+///     .sources(vec![TokenSource::cookie("access_token")])
+///     .renewal(JwtRenewal::cookie(60 * 60 * 24, "access_token").secure(true));
+///
+/// // 通过 X-Renewed-Token 响应头返回 由前端替换本地 token
+/// JwtAuth::<Claims>::new(vec!["/login"])
+///     .renewal(JwtRenewal::header(60 * 60 * 24, "X-Renewed-Token"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct JwtRenewal {
+    window: u64,
+    target: RenewTarget,
+    path: Cow<'static, str>,
+    secure: bool,
+}
+
+impl JwtRenewal {
+    pub fn new(window: u64, target: RenewTarget) -> Self {
+        Self {
+            window,
+            target,
+            path: Cow::Borrowed("/"),
+            secure: false,
+        }
+    }
+
+    /// 通过响应头返回新 token
+    pub fn header(window: u64, name: &'static str) -> Self {
+        Self::new(window, RenewTarget::Header(Cow::Borrowed(name)))
+    }
+
+    /// 通过 `Set-Cookie` 返回新 token
+    pub fn cookie(window: u64, name: &'static str) -> Self {
+        Self::new(window, RenewTarget::Cookie(Cow::Borrowed(name)))
+    }
+
+    /// cookie 的 Path 默认 `/`
+    pub fn path<P: Into<Cow<'static, str>>>(mut self, path: P) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// cookie 是否仅通过 https 发送
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.secure = secure;
+        self
+    }
+
+    /// 是否已进入续期窗口 claims 没有 `exp` 时不续期
+    pub fn needs_renewal<T: Serialize>(&self, claims: &T) -> bool {
+        let exp = serde_json::to_value(claims)
+            .ok()
+            .and_then(|claims| claims.get("exp").and_then(|exp| exp.as_u64()));
+        exp.is_some_and(|exp| exp.saturating_sub(timestamp()) <= self.window)
+    }
+
+    /// 将新 token 写入响应 `max_age` 为 cookie 有效期
+    pub(crate) fn apply(&self, res: &mut Response, token: &str, max_age: u64) {
+        let (name, value) = match &self.target {
+            RenewTarget::Header(name) => (HeaderName::try_from(name.as_ref()), token.to_string()),
+            RenewTarget::Cookie(name) => {
+                let secure = if self.secure { "; Secure" } else { "" };
+                let cookie = format!(
+                    "{name}={token}; Path={}; Max-Age={max_age}; HttpOnly; SameSite=Lax{secure}",
+                    self.path
+                );
+                (Ok(SET_COOKIE), cookie)
+            }
+        };
+
+        match (name, HeaderValue::try_from(value)) {
+            (Ok(name), Ok(value)) => {
+                res.headers_mut().append(name, value);
+            }
+            _ => Log::warn(format!("token 续期失败 无效的返回位置 {:?}", self.target)),
+        }
+    }
+}
+
+/// 续期 claims 并重新签发
+pub(crate) fn reissue<T: JwtRefresh>(
+    claims: &T,
+    keys: &JwtKeys,
+    duration: u64,
+) -> Result<String, Res<()>> {
+    let mut claims = claims.clone();
+    claims.renew(timestamp() + duration);
+    claims.encode_with(keys)
+}
+
+#[test]
+fn sliding_renewal() {
+    use axum::{
+        body::Body,
+        http::{header::COOKIE, Request},
+        routing::get,
+        Router,
+    };
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    use super::{JwtAuth, JwtToken, TokenSource};
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    struct Claims {
+        exp: u64,
+    }
+
+    impl JwtToken for Claims {
+        const DURATION: u64 = 1000;
+    }
+    impl JwtRefresh for Claims {
+        fn renew(&mut self, exp: u64) {
+            self.exp = exp;
+        }
+    }
+
+    let renewal = JwtRenewal::header(100, "X-Renewed-Token");
+    let expiring = Claims {
+        exp: timestamp() + 50,
+    };
+    assert!(renewal.needs_renewal(&expiring));
+    let fresh = Claims {
+        exp: Claims::expiration(),
+    };
+    assert!(!renewal.needs_renewal(&fresh));
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let app = Router::new()
+            .route("/", get(|| async {}))
+            .layer(JwtAuth::<Claims>::new(vec![]).renewal(renewal));
+        let call = |app: Router, token: String| async move {
+            let req = Request::get("/").header("Authorization", format!("Bearer {token}"));
+            app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap()
+        };
+
+        // 临近过期时通过响应头返回新 token
+        let res = call(app.clone(), expiring.encode().unwrap()).await;
+        let token = res.headers()["X-Renewed-Token"].to_str().unwrap();
+        let renewed = Claims::default().decode(token).unwrap();
+        assert!(renewed.exp + 5 >= Claims::expiration());
+        let res = call(app, token.to_string()).await;
+        assert!(!res.headers().contains_key("X-Renewed-Token"));
+
+        // 写回同名 cookie
+        let app = Router::new().route("/", get(|| async {})).layer(
+            JwtAuth::<Claims>::new(vec![])
+                .sources(vec![TokenSource::cookie("access_token")])
+                .renewal(JwtRenewal::cookie(100, "access_token").path("/api")),
+        );
+        let cookie = format!("access_token={}", expiring.encode().unwrap());
+        let req = Request::get("/").header(COOKIE, cookie);
+        let res = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+        let cookie = res.headers()[SET_COOKIE].to_str().unwrap();
+        assert!(cookie.starts_with("access_token=ey"));
+        assert!(cookie.ends_with("; Path=/api; Max-Age=1000; HttpOnly; SameSite=Lax"));
+    });
+}
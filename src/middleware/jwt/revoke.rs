@@ -0,0 +1,313 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use axum::async_trait;
+use bb8::Pool;
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Bool, Nullable, Text},
+    QueryableByName,
+};
+
+use super::{timestamp, JwtToken};
+use crate::{
+    database::postgres::{db_error, PgPool},
+    res::Res,
+};
+
+/// 已吊销 token 存储
+///
+/// 记录在 `until` 之后即可清除 此时对应 token 已经过期
+#[async_trait]
+pub trait RevocationStore: Send + Sync + 'static {
+    /// 吊销单个 token
+    async fn revoke_token(&self, jti: &str, until: u64) -> Result<(), Res<()>>;
+
+    /// 吊销主体在 `revoked_at` 之前签发的所有 token
+    ///
+    /// `iat` 精度为秒 吊销当秒重新签发的 token 不受影响
+    async fn revoke_subject(&self, sub: &str, revoked_at: u64, until: u64) -> Result<(), Res<()>>;
+
+    /// token 是否已被吊销
+    ///
+    /// 主体已被吊销且 token 没有签发时间时视为已吊销
+    async fn is_revoked(
+        &self,
+        jti: Option<&str>,
+        sub: Option<&str>,
+        iat: Option<u64>,
+    ) -> Result<bool, Res<()>>;
+}
+
+/// 内存吊销存储 适用于单实例部署
+#[derive(Default)]
+pub struct MemoryRevocationStore {
+    revoked: Mutex<Revoked>,
+}
+
+#[derive(Default)]
+struct Revoked {
+    /// jti -> until
+    tokens: HashMap<String, u64>,
+    /// sub -> (revoked_at, until)
+    subjects: HashMap<String, (u64, u64)>,
+}
+
+impl MemoryRevocationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RevocationStore for MemoryRevocationStore {
+    async fn revoke_token(&self, jti: &str, until: u64) -> Result<(), Res<()>> {
+        let mut revoked = self.revoked.lock().unwrap_or_else(|err| err.into_inner());
+        revoked.purge(timestamp());
+        revoked.tokens.insert(jti.to_string(), until);
+        Ok(())
+    }
+
+    async fn revoke_subject(&self, sub: &str, revoked_at: u64, until: u64) -> Result<(), Res<()>> {
+        let mut revoked = self.revoked.lock().unwrap_or_else(|err| err.into_inner());
+        revoked.purge(timestamp());
+        revoked
+            .subjects
+            .insert(sub.to_string(), (revoked_at, until));
+        Ok(())
+    }
+
+    async fn is_revoked(
+        &self,
+        jti: Option<&str>,
+        sub: Option<&str>,
+        iat: Option<u64>,
+    ) -> Result<bool, Res<()>> {
+        let revoked = self.revoked.lock().unwrap_or_else(|err| err.into_inner());
+        let now = timestamp();
+
+        let token = jti
+            .and_then(|jti| revoked.tokens.get(jti))
+            .is_some_and(|until| *until > now);
+        let subject = sub
+            .and_then(|sub| revoked.subjects.get(sub))
+            .is_some_and(|(at, until)| *until > now && iat.is_none_or(|iat| iat < *at));
+
+        Ok(token || subject)
+    }
+}
+
+impl Revoked {
+    fn purge(&mut self, now: u64) {
+        self.tokens.retain(|_, until| *until > now);
+        self.subjects.retain(|_, (_, until)| *until > now);
+    }
+}
+
+/// Postgres 吊销存储 多实例共享
+///
+/// 使用前需调用 `migrate` 创建 `jwt_revocations` 表
+pub struct PgRevocationStore {
+    pool: Pool<PgPool>,
+}
+
+#[derive(QueryableByName)]
+struct Exists {
+    #[diesel(sql_type = Bool)]
+    revoked: bool,
+}
+
+impl PgRevocationStore {
+    pub fn new(pool: Pool<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// 创建 `jwt_revocations` 表
+    pub async fn migrate(&self) -> Result<(), Res<()>> {
+        // 仅在数据库操作中引入 其 `load` 会遮蔽原子类型的同名方法
+        use diesel_async::RunQueryDsl;
+
+        let mut conn = self.pool.get().await.map_err(db_error)?;
+        sql_query(
+            "CREATE TABLE IF NOT EXISTS jwt_revocations (
+                kind TEXT NOT NULL,
+                id TEXT NOT NULL,
+                revoked_at BIGINT NOT NULL,
+                until BIGINT NOT NULL,
+                PRIMARY KEY (kind, id)
+            )",
+        )
+        .execute(&mut conn)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn insert(
+        &self,
+        kind: &str,
+        id: &str,
+        revoked_at: u64,
+        until: u64,
+    ) -> Result<(), Res<()>> {
+        use diesel_async::RunQueryDsl;
+
+        let mut conn = self.pool.get().await.map_err(db_error)?;
+        sql_query("DELETE FROM jwt_revocations WHERE until <= $1")
+            .bind::<BigInt, _>(timestamp() as i64)
+            .execute(&mut conn)
+            .await
+            .map_err(db_error)?;
+
+        sql_query(
+            "INSERT INTO jwt_revocations (kind, id, revoked_at, until) VALUES ($1, $2, $3, $4)
+            ON CONFLICT (kind, id) DO UPDATE SET revoked_at = EXCLUDED.revoked_at, until = EXCLUDED.until",
+        )
+        .bind::<Text, _>(kind)
+        .bind::<Text, _>(id)
+        .bind::<BigInt, _>(revoked_at as i64)
+        .bind::<BigInt, _>(until as i64)
+        .execute(&mut conn)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RevocationStore for PgRevocationStore {
+    async fn revoke_token(&self, jti: &str, until: u64) -> Result<(), Res<()>> {
+        self.insert("jti", jti, timestamp(), until).await
+    }
+
+    async fn revoke_subject(&self, sub: &str, revoked_at: u64, until: u64) -> Result<(), Res<()>> {
+        self.insert("sub", sub, revoked_at, until).await
+    }
+
+    async fn is_revoked(
+        &self,
+        jti: Option<&str>,
+        sub: Option<&str>,
+        iat: Option<u64>,
+    ) -> Result<bool, Res<()>> {
+        use diesel_async::RunQueryDsl;
+
+        if jti.is_none() && sub.is_none() {
+            return Ok(false);
+        }
+
+        let mut conn = self.pool.get().await.map_err(db_error)?;
+        let res = sql_query(
+            "SELECT EXISTS (
+                SELECT 1 FROM jwt_revocations WHERE until > $1 AND (
+                    (kind = 'jti' AND id = $2)
+                    OR (kind = 'sub' AND id = $3 AND ($4 IS NULL OR $4 < revoked_at))
+                )
+            ) AS revoked",
+        )
+        .bind::<BigInt, _>(timestamp() as i64)
+        .bind::<Nullable<Text>, _>(jti)
+        .bind::<Nullable<Text>, _>(sub)
+        .bind::<Nullable<BigInt>, _>(iat.map(|iat| iat as i64))
+        .get_result::<Exists>(&mut conn)
+        .await
+        .map_err(db_error)?;
+
+        Ok(res.revoked)
+    }
+}
+
+/// token 吊销
+///
+/// 配置到 `JwtAuth` 后 每次请求都会检查 token 是否已被吊销 并放入请求扩展供 handler 使用
+///
+/// # Examples
+/// ```ignore
+/// let revocation = JwtRevocation::new(MemoryRevocationStore::new());
+/// let app = Router::new()
+///     .route("/logout", post(logout))
+///     .layer(JwtAuth::<Claims>::new(vec!["/login"]).revocation(revocation));
+///
+/// async fn logout(
+///     Extension(revocation): Extension<JwtRevocation>,
+///     Extension(claims): Extension<Claims>,
+/// ) -> utils::Result<()> {
+///     revocation.revoke(&claims).await?;
+///     Ok(Res::ok(()))
+/// }
+/// ```
+#[derive(Clone)]
+pub struct JwtRevocation {
+    store: Arc<dyn RevocationStore>,
+    /// 吊销记录的最短保留时间 单位 s
+    retention: Arc<AtomicU64>,
+}
+
+impl JwtRevocation {
+    pub fn new<R: RevocationStore>(store: R) -> Self {
+        Self {
+            store: Arc::new(store),
+            retention: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// 吊销记录的最短保留时间 需覆盖最长的 token 有效期 单位 s
+    ///
+    /// 默认取 `T::DURATION` 配置到 `JwtRefresher` 时自动覆盖 `REFRESH_DURATION`
+    /// 配置到带 `JwtTenants` 的 `JwtAuth` 时自动覆盖当时已有租户的有效期 运行时添加更长有效期的租户需手动设置
+    pub fn retention(self, retention: u64) -> Self {
+        self.extend(retention);
+        self
+    }
+
+    /// 延长最短保留时间 已更长时不变
+    pub(crate) fn extend(&self, retention: u64) {
+        self.retention.fetch_max(retention, Ordering::Relaxed);
+    }
+
+    /// 吊销单个 token claims 需实现 `JwtToken::jti` 记录保留至 token 的 `exp`
+    pub async fn revoke<T: JwtToken>(&self, claims: &T) -> Result<(), Res<()>> {
+        let jti = claims
+            .jti()
+            .ok_or_else(|| Res::internal_error("claims 未提供 jti 无法吊销"))?;
+        let exp = serde_json::to_value(claims)
+            .ok()
+            .and_then(|claims| claims.get("exp")?.as_u64());
+        match exp {
+            Some(exp) => self.store.revoke_token(&jti, exp).await,
+            None => self.revoke_jti::<T>(&jti).await,
+        }
+    }
+
+    /// 按 jti 吊销单个 token 不知道 token 的 `exp` 时按最长有效期保留记录
+    pub async fn revoke_jti<T: JwtToken>(&self, jti: &str) -> Result<(), Res<()>> {
+        self.store.revoke_token(jti, self.until::<T>()).await
+    }
+
+    /// 吊销主体当前已签发的所有 token claims 需实现 `JwtToken::sub` 与 `JwtToken::iat`
+    ///
+    /// 配置到 `JwtRefresher` 后主体的 refresh token 也随之失效
+    pub async fn revoke_subject<T: JwtToken>(&self, sub: &str) -> Result<(), Res<()>> {
+        self.store
+            .revoke_subject(sub, timestamp(), self.until::<T>())
+            .await
+    }
+
+    /// 吊销记录的清除时间 此时吊销前签发的 token 均已过期
+    fn until<T: JwtToken>(&self) -> u64 {
+        timestamp() + T::DURATION.max(self.retention.load(Ordering::Relaxed))
+    }
+
+    /// token 是否已被吊销
+    pub async fn is_revoked<T: JwtToken>(&self, claims: &T) -> Result<bool, Res<()>> {
+        let (jti, sub) = (claims.jti(), claims.sub());
+        self.store
+            .is_revoked(jti.as_deref(), sub.as_deref(), claims.iat())
+            .await
+    }
+}
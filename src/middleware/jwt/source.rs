@@ -0,0 +1,106 @@
+use std::borrow::Cow;
+
+use axum::{
+    headers::{authorization::Bearer, Authorization, Cookie, HeaderMapExt},
+    http::{HeaderMap, Uri},
+};
+
+/// token 的读取位置
+///
+/// # Examples
+/// ```
+/// use mll_axum_utils::middleware::jwt::TokenSource;
+/// // 依次尝试: Authorization 请求头 -> access_token cookie -> ?token= 查询参数 -> X-Token 请求头
+/// const SOURCES: &[TokenSource] = &[
+///     TokenSource::Bearer,
+///     TokenSource::cookie("access_token"),
+///     TokenSource::query("token"),
+///     TokenSource::header("X-Token"),
+/// ];
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenSource {
+    /// `Authorization: Bearer <token>`
+    Bearer,
+    /// 自定义请求头 值为 token 本身 也可带 `Bearer ` 前缀
+    Header(Cow<'static, str>),
+    /// cookie
+    Cookie(Cow<'static, str>),
+    /// 查询参数
+    Query(Cow<'static, str>),
+}
+
+impl TokenSource {
+    pub const fn header(name: &'static str) -> Self {
+        Self::Header(Cow::Borrowed(name))
+    }
+
+    pub const fn cookie(name: &'static str) -> Self {
+        Self::Cookie(Cow::Borrowed(name))
+    }
+
+    pub const fn query(name: &'static str) -> Self {
+        Self::Query(Cow::Borrowed(name))
+    }
+
+    /// 从请求中读取 token
+    pub fn extract(&self, headers: &HeaderMap, uri: &Uri) -> Option<String> {
+        let token = match self {
+            TokenSource::Bearer => headers
+                .typed_get::<Authorization<Bearer>>()
+                .map(|auth| auth.token().to_string()),
+            TokenSource::Header(name) => {
+                let value = headers.get(name.as_ref())?.to_str().ok()?.trim();
+                let token = value.strip_prefix("Bearer ").unwrap_or(value);
+                Some(token.trim().to_string())
+            }
+            TokenSource::Cookie(name) => headers
+                .typed_get::<Cookie>()
+                .and_then(|cookie| cookie.get(name).map(String::from)),
+            TokenSource::Query(name) => {
+                let query = uri.query()?;
+                let pairs = serde_urlencoded::from_str::<Vec<(String, String)>>(query).ok()?;
+                pairs.into_iter().find(|(k, _)| k == name).map(|(_, v)| v)
+            }
+        };
+        token.filter(|token| !token.is_empty())
+    }
+}
+
+/// 按顺序从请求中读取第一个 token
+pub fn extract_token(sources: &[TokenSource], headers: &HeaderMap, uri: &Uri) -> Option<String> {
+    sources
+        .iter()
+        .find_map(|source| source.extract(headers, uri))
+}
+
+#[test]
+fn token_sources() {
+    use axum::http::header::{AUTHORIZATION, COOKIE};
+
+    const SOURCES: &[TokenSource] = &[
+        TokenSource::Bearer,
+        TokenSource::cookie("access_token"),
+        TokenSource::query("token"),
+        TokenSource::header("X-Token"),
+    ];
+    let uri: Uri = "/a?page=1&token=q%2Bt".parse().unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert(AUTHORIZATION, "Bearer b".parse().unwrap());
+    headers.insert(COOKIE, "theme=dark; access_token=c".parse().unwrap());
+    headers.insert("X-Token", "Bearer h".parse().unwrap());
+
+    // 按顺序读取第一个 token
+    assert_eq!(extract_token(SOURCES, &headers, &uri).unwrap(), "b");
+    headers.remove(AUTHORIZATION);
+    assert_eq!(extract_token(SOURCES, &headers, &uri).unwrap(), "c");
+    headers.remove(COOKIE);
+    assert_eq!(extract_token(SOURCES, &headers, &uri).unwrap(), "q+t");
+    let uri: Uri = "/a?token=".parse().unwrap();
+    assert_eq!(extract_token(SOURCES, &headers, &uri).unwrap(), "h");
+    headers.insert("X-Token", "h2".parse().unwrap());
+    assert_eq!(extract_token(SOURCES, &headers, &uri).unwrap(), "h2");
+
+    // 未配置的位置不读取
+    assert_eq!(extract_token(&[TokenSource::Bearer], &headers, &uri), None);
+}
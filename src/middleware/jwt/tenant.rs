@@ -0,0 +1,366 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use axum::http::{header::HOST, HeaderMap, Uri};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{timestamp, JwtKeys, JwtRejection, JwtToken};
+use crate::res::Res;
+
+/// 租户标识的读取位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TenantSource {
+    /// 子域名 值为主域名 `a.example.com` 的租户为 `a`
+    Subdomain(Cow<'static, str>),
+    /// 请求头
+    Header(Cow<'static, str>),
+    /// 未验证 token 中的 claim 用于选择密钥 token 仍需通过该租户密钥的验证
+    /// 且验证后该 claim 需与所选租户一致
+    Claim(Cow<'static, str>),
+}
+
+impl TenantSource {
+    pub const fn subdomain(domain: &'static str) -> Self {
+        Self::Subdomain(Cow::Borrowed(domain))
+    }
+
+    pub const fn header(name: &'static str) -> Self {
+        Self::Header(Cow::Borrowed(name))
+    }
+
+    pub const fn claim(name: &'static str) -> Self {
+        Self::Claim(Cow::Borrowed(name))
+    }
+
+    /// 从请求中读取租户标识
+    pub fn extract(&self, headers: &HeaderMap, uri: &Uri, token: Option<&str>) -> Option<String> {
+        match self {
+            TenantSource::Subdomain(domain) => {
+                let host = match uri.host() {
+                    Some(host) => host,
+                    None => headers.get(HOST)?.to_str().ok()?,
+                };
+                let host = host.split(':').next()?;
+                let tenant = host.strip_suffix(domain.as_ref())?.strip_suffix('.')?;
+                (!tenant.is_empty()).then(|| tenant.to_string())
+            }
+            TenantSource::Header(name) => {
+                let tenant = headers.get(name.as_ref())?.to_str().ok()?.trim();
+                (!tenant.is_empty()).then(|| tenant.to_string())
+            }
+            TenantSource::Claim(name) => {
+                let payload = token?.split('.').nth(1)?;
+                let payload = URL_SAFE_NO_PAD.decode(payload).ok()?;
+                let claims = serde_json::from_slice::<Value>(&payload).ok()?;
+                claim_tenant(&claims, name)
+            }
+        }
+    }
+}
+
+/// 读取 claims 中的租户标识
+fn claim_tenant(claims: &Value, name: &str) -> Option<String> {
+    match claims.get(name)? {
+        Value::String(tenant) => Some(tenant.clone()),
+        Value::Number(tenant) => Some(tenant.to_string()),
+        _ => None,
+    }
+}
+
+/// 租户的密钥与 token 有效期
+pub struct JwtTenant {
+    keys: Arc<JwtKeys>,
+    duration: Option<u64>,
+}
+
+impl JwtTenant {
+    pub fn new<K: Into<JwtKeys>>(keys: K) -> Self {
+        Self {
+            keys: Arc::new(keys.into()),
+            duration: None,
+        }
+    }
+
+    /// token 持续时间 未设置时使用 `T::DURATION` 单位 s
+    pub fn duration(mut self, duration: u64) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    pub fn keys(&self) -> Arc<JwtKeys> {
+        self.keys.clone()
+    }
+
+    /// 该租户 token 的持续时间 单位 s
+    pub fn lifetime<T: JwtToken>(&self) -> u64 {
+        self.duration.unwrap_or(T::DURATION)
+    }
+
+    /// 该租户 token 的过期时间 签发 token 时用于设置 `exp`
+    pub fn expiration<T: JwtToken>(&self) -> u64 {
+        timestamp() + self.lifetime::<T>()
+    }
+}
+
+/// 当前请求所属的租户 由 `JwtAuth` 放入请求扩展
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TenantId(pub String);
+
+/// 多租户密钥 每个请求按租户标识选择密钥集合
+///
+/// 租户之间的密钥互不相通 为租户 A 签发的 token 无法通过租户 B 的验证
+///
+/// 租户可能共用密钥时 通过 `Self::claim` 在 token 中记录租户 验证后校验其与请求的租户一致
+///
+/// # Examples
+/// ```ignore
+/// let tenants = JwtTenants::new(TenantSource::subdomain("example.com"))
+///     .tenant("a", JwtTenant::new(JwtKey::from_env("TENANT_A_SECRET")?))
+///     .tenant("b", JwtTenant::new(JwtKey::from_env("TENANT_B_SECRET")?).duration(60 * 60))
+///     .claim("tenant");
+///
+/// let app = Router::new()
+///     .route("/login", post(login))
+///     .layer(JwtAuth::<Claims>::new(vec!["/login"]).tenants(tenants.clone()))
+///     .layer(Extension(tenants));
+///
+/// async fn login(
+///     Extension(tenants): Extension<JwtTenants>,
+///     VJsonOrForm(login): VJsonOrForm<Login>,
+/// ) -> utils::Result<String> {
+///     let tenant = tenants.get(&login.tenant).ok_or(Res::error("未知的租户"))?;
+///     let claims = Claims {
+///         exp: tenant.expiration::<Claims>(),
+///         tenant: login.tenant.clone(),
+///         user: login.user,
+///     };
+///     Ok(Res::ok(tenants.encode(&login.tenant, &claims)?))
+/// }
+/// ```
+#[derive(Clone)]
+pub struct JwtTenants {
+    source: Arc<TenantSource>,
+    claim: Option<Cow<'static, str>>,
+    tenants: Arc<RwLock<HashMap<String, Arc<JwtTenant>>>>,
+}
+
+impl JwtTenants {
+    pub fn new(source: TenantSource) -> Self {
+        let claim = match &source {
+            TenantSource::Claim(name) => Some(name.clone()),
+            _ => None,
+        };
+        Self {
+            source: Arc::new(source),
+            claim,
+            tenants: Default::default(),
+        }
+    }
+
+    /// token 中记录租户的 claim 验证通过后该 claim 需与请求的租户一致
+    ///
+    /// 使用 `TenantSource::Claim` 时默认为同名 claim
+    pub fn claim(mut self, name: &'static str) -> Self {
+        self.claim = Some(Cow::Borrowed(name));
+        self
+    }
+
+    /// 添加租户
+    pub fn tenant<I: Into<String>>(self, id: I, tenant: JwtTenant) -> Self {
+        self.insert(id, tenant);
+        self
+    }
+
+    /// 运行时添加或替换租户
+    pub fn insert<I: Into<String>>(&self, id: I, tenant: JwtTenant) {
+        let mut tenants = self.tenants.write().unwrap_or_else(|err| err.into_inner());
+        tenants.insert(id.into(), Arc::new(tenant));
+    }
+
+    /// 运行时移除租户 其 token 随即失效
+    pub fn remove(&self, id: &str) {
+        let mut tenants = self.tenants.write().unwrap_or_else(|err| err.into_inner());
+        tenants.remove(id);
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<JwtTenant>> {
+        let tenants = self.tenants.read().unwrap_or_else(|err| err.into_inner());
+        tenants.get(id).cloned()
+    }
+
+    /// 已有租户中最长的 token 持续时间 单位 s
+    pub(crate) fn max_lifetime<T: JwtToken>(&self) -> u64 {
+        let tenants = self.tenants.read().unwrap_or_else(|err| err.into_inner());
+        let lifetimes = tenants.values().map(|tenant| tenant.lifetime::<T>());
+        lifetimes.max().unwrap_or(T::DURATION)
+    }
+
+    /// 使用租户的签名密钥编码 token
+    pub fn encode<T: JwtToken>(&self, id: &str, claims: &T) -> Result<String, Res<()>> {
+        let tenant = self
+            .get(id)
+            .ok_or_else(|| Res::error(format!("未知的租户 {id}")))?;
+        claims.encode_with(&tenant.keys)
+    }
+
+    /// 按请求选择租户
+    pub(crate) fn resolve(
+        &self,
+        headers: &HeaderMap,
+        uri: &Uri,
+        token: Option<&str>,
+    ) -> Result<(TenantId, Arc<JwtTenant>), JwtRejection> {
+        if token.is_none() {
+            return Err(JwtRejection::Missing);
+        }
+        let id = self
+            .source
+            .extract(headers, uri, token)
+            .ok_or(JwtRejection::UnknownTenant)?;
+        let tenant = self.get(&id).ok_or(JwtRejection::UnknownTenant)?;
+        Ok((TenantId(id), tenant))
+    }
+
+    /// 检查已验证的 claims 中记录的租户与请求的租户一致
+    pub(crate) fn check<T: Serialize>(
+        &self,
+        id: &TenantId,
+        claims: &T,
+    ) -> Result<(), JwtRejection> {
+        let Some(name) = &self.claim else {
+            return Ok(());
+        };
+        let claims = serde_json::to_value(claims).map_err(|_| JwtRejection::InvalidClaims)?;
+        match claim_tenant(&claims, name) {
+            Some(tenant) if tenant == id.0 => Ok(()),
+            _ => Err(JwtRejection::UnknownTenant),
+        }
+    }
+}
+
+#[test]
+fn tenant_keys() {
+    use axum::{
+        body::Body,
+        extract::Extension,
+        http::{Request, StatusCode},
+        routing::get,
+        Router,
+    };
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    use super::{JwtAuth, JwtKey, JwtRefresh, JwtRenewal};
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    struct Claims {
+        exp: u64,
+        tenant: String,
+    }
+
+    impl JwtToken for Claims {}
+    impl JwtRefresh for Claims {
+        fn renew(&mut self, exp: u64) {
+            self.exp = exp;
+        }
+    }
+
+    let tenants = JwtTenants::new(TenantSource::header("X-Tenant"))
+        .tenant("a", JwtTenant::new(JwtKey::from_secret("a")))
+        .tenant("b", JwtTenant::new(JwtKey::from_secret("b")).duration(3600))
+        .claim("tenant");
+    let b = tenants.get("b").unwrap();
+    assert_eq!(b.lifetime::<Claims>(), 3600);
+    assert_eq!(tenants.max_lifetime::<Claims>(), Claims::DURATION);
+    tenants.insert(
+        "c",
+        JwtTenant::new(JwtKey::from_secret("c")).duration(u32::MAX.into()),
+    );
+    assert_eq!(tenants.max_lifetime::<Claims>(), u32::MAX as u64);
+    tenants.remove("c");
+
+    let claims = |tenant: &str, exp: u64| Claims {
+        exp,
+        tenant: tenant.into(),
+    };
+    let token_a = tenants.encode("a", &claims("a", Claims::expiration()));
+    let token_a = token_a.unwrap();
+    // 即将过期 续期时使用租户 b 的有效期
+    let token_b = tenants.encode("b", &claims("b", timestamp() + 10)).unwrap();
+    // 使用租户 b 的密钥签发但 claims 记录为租户 a
+    let mixed = tenants.encode("b", &claims("a", Claims::expiration()));
+    let mixed = mixed.unwrap();
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let app = Router::new()
+            .route(
+                "/",
+                get(|Extension(TenantId(id)): Extension<TenantId>| async move { id }),
+            )
+            .layer(
+                JwtAuth::<Claims>::new(vec![])
+                    .tenants(tenants.clone())
+                    .renewal(JwtRenewal::header(60, "X-Renewed-Token")),
+            );
+        let call = |tenant: &'static str, token: &str| {
+            let req = Request::get("/")
+                .header("X-Tenant", tenant)
+                .header("Authorization", format!("Bearer {token}"));
+            app.clone().oneshot(req.body(Body::empty()).unwrap())
+        };
+        let code = |res: axum::response::Response| async move {
+            assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+            let mut body = res.into_body();
+            let body = axum::body::HttpBody::data(&mut body)
+                .await
+                .unwrap()
+                .unwrap();
+            let mut body: Value = serde_json::from_slice(&body).unwrap();
+            body["data"]["code"].take()
+        };
+
+        let res = call("a", &token_a).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(!res.headers().contains_key("X-Renewed-Token"));
+        let mut body = res.into_body();
+        let body = axum::body::HttpBody::data(&mut body).await.unwrap();
+        assert_eq!(body.unwrap(), "a");
+
+        let res = call("b", &token_b).await.unwrap();
+        let renewed = res.headers()["X-Renewed-Token"].to_str().unwrap();
+        let renewed = Claims::default().decode_with(renewed, &b.keys()).unwrap();
+        assert!(renewed.exp <= timestamp() + 3600);
+        assert!(renewed.exp + 5 >= b.expiration::<Claims>());
+
+        // 租户之间的密钥互不相通
+        let res = call("b", &token_a).await.unwrap();
+        assert_eq!(code(res).await, JwtRejection::BadSignature.code());
+        let res = call("b", &mixed).await.unwrap();
+        assert_eq!(code(res).await, JwtRejection::UnknownTenant.code());
+        let res = call("c", &token_a).await.unwrap();
+        assert_eq!(code(res).await, JwtRejection::UnknownTenant.code());
+
+        // 移除租户后 token 随即失效
+        tenants.remove("a");
+        let res = call("a", &token_a).await.unwrap();
+        assert_eq!(code(res).await, JwtRejection::UnknownTenant.code());
+    });
+
+    let mut headers = HeaderMap::new();
+    headers.insert(HOST, "a.example.com:3000".parse().unwrap());
+    let uri = Uri::from_static("/");
+    let subdomain = TenantSource::subdomain("example.com");
+    assert_eq!(subdomain.extract(&headers, &uri, None).unwrap(), "a");
+    headers.insert(HOST, "example.com".parse().unwrap());
+    assert_eq!(subdomain.extract(&headers, &uri, None), None);
+    let claim = TenantSource::claim("tenant");
+    assert_eq!(claim.extract(&headers, &uri, Some(&mixed)).unwrap(), "a");
+}
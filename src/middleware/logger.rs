@@ -1,14 +1,12 @@
 use std::{
     fs::File,
     io::Write,
-    net::SocketAddr,
     sync::mpsc::{channel, Sender},
     task::{Context, Poll},
 };
 
 use axum::{
     body::Body,
-    extract::ConnectInfo,
     http::{header::LOCATION, Request},
     response::Response,
 };
@@ -18,6 +16,7 @@ use futures_util::future::BoxFuture;
 use percent_encoding::percent_decode;
 use tower::{Layer, Service};
 
+use super::ip::ClientIp;
 use crate::utils::create_log_file;
 
 /// # Examples
@@ -121,10 +120,10 @@ where
         let begin = Local::now();
         // 请求方式
         let method = req.method().to_string();
-        // 连接 ip
-        let ip = match req.extensions().get::<ConnectInfo<SocketAddr>>() {
-            Some(v) => v.0.ip().to_string(),
-            None => panic!("Axum service 未配置 ConnectInfo<SocketAddr>"),
+        // 客户端 ip 未配置 ConnectInfo<SocketAddr> 时为 `-`
+        let ip = match ClientIp::from_extensions(req.extensions()) {
+            Some(ip) => ip.0.to_string(),
+            None => "-".into(),
         };
         // 请求路径 解码为 utf-8
         let mut path = percent_decode(req.uri().path().as_bytes())
@@ -1,3 +1,11 @@
+pub mod api_key;
+pub mod basic_auth;
+pub mod concurrency;
+pub mod csrf;
+pub mod filter;
+pub mod ip;
 pub mod jwt;
 pub mod logger;
 pub mod interceptor;
+pub mod rate_limit;
+pub mod session;
@@ -0,0 +1,282 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use axum::{
+    body::Body,
+    http::{header::RETRY_AFTER, HeaderMap, HeaderValue, Request},
+    response::{IntoResponse, Response},
+};
+use futures_util::future::BoxFuture;
+use serde::Serialize;
+use serde_json::Value;
+use tower::{Layer, Service};
+
+use super::{
+    filter::{FilterMode, PathFilter},
+    ip::ClientIp,
+};
+use crate::{log::Log, res::Res, utils::timestamp_millis};
+
+mod store;
+
+pub use store::*;
+
+type KeyFn = Arc<dyn Fn(&Request<Body>) -> Option<String> + Send + Sync>;
+
+/// 限流的计数对象
+#[derive(Clone)]
+pub enum RateKey {
+    /// 客户端 ip 位于代理之后时配合 `ClientIpLayer` 使用
+    Ip,
+    /// 自定义 返回 None 时该规则不限制此请求
+    Custom(KeyFn),
+}
+
+impl RateKey {
+    /// 自定义计数对象
+    /// # Examples
+    /// ```ignore
+    /// RateKey::custom(|req| {
+    ///     let key = req.headers().get("X-Api-Key")?;
+    ///     key.to_str().ok().map(String::from)
+    /// });
+    /// ```
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(&Request<Body>) -> Option<String> + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(f))
+    }
+
+    /// 按 `JwtAuth` 放入请求扩展的 claims 中的字段计数 如用户 id
+    ///
+    /// 需位于 `JwtAuth` 内层 未登录的请求不受该规则限制
+    /// # Examples
+    /// ```ignore
+    /// RateKey::claim::<Claims>("uid");
+    /// ```
+    pub fn claim<T: Serialize + Send + Sync + 'static>(name: &'static str) -> Self {
+        Self::custom(move |req| {
+            let claims = serde_json::to_value(req.extensions().get::<T>()?).ok()?;
+            match claims.get(name)? {
+                Value::String(value) => Some(value.clone()),
+                Value::Null => None,
+                value => Some(value.to_string()),
+            }
+        })
+    }
+
+    fn key(&self, req: &Request<Body>) -> Option<String> {
+        match self {
+            RateKey::Ip => ClientIp::from_extensions(req.extensions()).map(|ip| ip.0.to_string()),
+            RateKey::Custom(f) => f(req),
+        }
+    }
+}
+
+/// 限流规则 每条规则独立计数 匹配的路由共享同一配额
+#[derive(Clone)]
+pub struct RateRule {
+    name: String,
+    quota: Quota,
+    key: RateKey,
+    filter: Arc<PathFilter>,
+    mode: FilterMode,
+}
+
+impl RateRule {
+    /// `name` 用于区分存储中不同规则的计数
+    pub fn new<N: Into<String>>(name: N, quota: Quota) -> Self {
+        Self {
+            name: name.into(),
+            quota,
+            key: RateKey::Ip,
+            filter: Arc::new(PathFilter::new()),
+            mode: FilterMode::Exempt,
+        }
+    }
+
+    /// 计数对象 默认按 ip
+    pub fn key(mut self, key: RateKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// 仅匹配的路由生效
+    pub fn only(mut self, filter: PathFilter) -> Self {
+        self.filter = Arc::new(filter);
+        self.mode = FilterMode::Protect;
+        self
+    }
+
+    /// 匹配的路由不受该规则限制
+    pub fn exempt(mut self, filter: PathFilter) -> Self {
+        self.filter = Arc::new(filter);
+        self.mode = FilterMode::Exempt;
+        self
+    }
+}
+
+/// 限流中间件 使用 GCRA 算法
+///
+/// 请求需满足所有匹配规则的配额 超出时返回 429 与 `Retry-After` 被拒绝的请求不消耗任何配额
+/// 响应携带配额最紧张的规则的 `RateLimit-Limit` `RateLimit-Remaining` `RateLimit-Reset`
+///
+/// # Examples
+/// ```ignore
+/// let limit = RateLimit::new(MemoryRateLimitStore::new())
+///     .rule(RateRule::new("global", Quota::per_minute(300)))
+///     .rule(RateRule::new("login", Quota::per_minute(5)).only(PathFilter::from(vec!["POST /login"])))
+///     .rule(
+///         RateRule::new("user", Quota::per_second(10))
+///             .key(RateKey::claim::<Claims>("uid"))
+///             .only(PathFilter::new().rule(Rule::prefix("/api"))),
+///     );
+///
+/// let app = Router::new()
+///     .route("/login", post(login))
+///     .route("/api/orders", get(orders))
+///     .layer(limit)
+///     .layer(JwtAuth::<Claims>::new(vec!["/login"]).optional(true));
+/// ```
+#[derive(Clone)]
+pub struct RateLimit {
+    store: Arc<dyn RateLimitStore>,
+    rules: Arc<Vec<RateRule>>,
+    /// 存储是否不可用 仅在状态变化时记录日志
+    failing: Arc<AtomicBool>,
+}
+
+impl RateLimit {
+    pub fn new<R: RateLimitStore>(store: R) -> Self {
+        Self {
+            store: Arc::new(store),
+            rules: Arc::new(vec![]),
+            failing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// 添加规则
+    pub fn rule(mut self, rule: RateRule) -> Self {
+        Arc::make_mut(&mut self.rules).push(rule);
+        self
+    }
+}
+
+impl<S> Layer<S> for RateLimit {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            store: self.store.clone(),
+            rules: self.rules.clone(),
+            failing: self.failing.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    store: Arc<dyn RateLimitStore>,
+    rules: Arc<Vec<RateRule>>,
+    failing: Arc<AtomicBool>,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let checks: Vec<_> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.mode.applies(&rule.filter, &req))
+            .filter_map(|rule| {
+                let key = rule.key.key(&req)?;
+                Some((bucket(&rule.name, &key), rule.quota))
+            })
+            .collect();
+        if checks.is_empty() {
+            return Box::pin(self.inner.call(req));
+        }
+
+        let store = self.store.clone();
+        let failing = self.failing.clone();
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let decisions = match store.check(&checks, timestamp_millis()).await {
+                Ok(decisions) => {
+                    if failing.swap(false, Ordering::Relaxed) {
+                        Log::info("限流存储已恢复");
+                    }
+                    decisions
+                }
+                // 存储不可用时不限流 只在首次出错时记录日志
+                Err(err) => {
+                    if !failing.swap(true, Ordering::Relaxed) {
+                        Log::error(format!("限流存储出错 暂停限流 {err:?}"));
+                    }
+                    return inner.call(req).await;
+                }
+            };
+            if let Some(decision) = decisions.iter().find(|decision| !decision.allowed) {
+                return Ok(limited(decision));
+            }
+
+            let mut res = inner.call(req).await?;
+            // 配额最紧张的规则 相同时取先添加的
+            if let Some(decision) = decisions.iter().min_by_key(|decision| decision.remaining) {
+                set_headers(res.headers_mut(), decision);
+            }
+            Ok(res)
+        })
+    }
+}
+
+/// 存储中的计数键 规则名带长度前缀 名称含 `:` 时也不会与其他规则冲突
+fn bucket(name: &str, key: &str) -> String {
+    format!("{}:{name}:{key}", name.len())
+}
+
+/// 429 响应
+fn limited(decision: &Decision) -> Response {
+    let mut res = Res::<()>::new(429u16, "请求过于频繁").into_response();
+    let headers = res.headers_mut();
+    set_headers(headers, decision);
+    headers.insert(RETRY_AFTER, HeaderValue::from(secs(decision.retry_after)));
+    res
+}
+
+fn set_headers(headers: &mut HeaderMap, decision: &Decision) {
+    headers.insert("RateLimit-Limit", HeaderValue::from(decision.limit));
+    headers.insert("RateLimit-Remaining", HeaderValue::from(decision.remaining));
+    headers.insert("RateLimit-Reset", HeaderValue::from(secs(decision.reset)));
+}
+
+/// 毫秒向上取整为秒
+fn secs(millis: u64) -> u64 {
+    millis.div_ceil(1000)
+}
+
+#[test]
+fn bucket_keys() {
+    assert_eq!(bucket("login", "1.2.3.4"), "5:login:1.2.3.4");
+    assert_ne!(bucket("a:b", "c"), bucket("a", "b:c"));
+}
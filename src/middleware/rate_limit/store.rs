@@ -0,0 +1,195 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use axum::async_trait;
+
+use crate::res::Res;
+
+/// 配额 `period` 内最多 `limit` 次请求 允许突发
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    limit: u32,
+    period: u64,
+}
+
+impl Quota {
+    /// `period` 单位 s
+    pub fn new(limit: u32, period: u64) -> Self {
+        Self {
+            limit: limit.max(1),
+            period: period.max(1) * 1000,
+        }
+    }
+
+    pub fn per_second(limit: u32) -> Self {
+        Self::new(limit, 1)
+    }
+
+    pub fn per_minute(limit: u32) -> Self {
+        Self::new(limit, 60)
+    }
+
+    pub fn per_hour(limit: u32) -> Self {
+        Self::new(limit, 60 * 60)
+    }
+
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// 周期 单位 ms
+    pub fn period(&self) -> u64 {
+        self.period
+    }
+
+    /// 每次请求消耗的时间 单位 ms
+    fn interval(&self) -> u64 {
+        (self.period / self.limit as u64).max(1)
+    }
+}
+
+/// 限流结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision {
+    /// 是否允许
+    pub allowed: bool,
+    /// 配额
+    pub limit: u32,
+    /// 剩余次数
+    pub remaining: u32,
+    /// 配额完全恢复的剩余时间 单位 ms
+    pub reset: u64,
+    /// 被拒绝时可重试的剩余时间 单位 ms
+    pub retry_after: u64,
+}
+
+/// GCRA 算法 `tat` 为理论到达时间 返回结果与新的 `tat`
+///
+/// 供自定义存储复用 存储需原子地读取各 key 的 `tat` 全部允许时才写入新的 `tat`
+pub fn gcra(quota: &Quota, tat: Option<u64>, now: u64) -> (Decision, Option<u64>) {
+    let interval = quota.interval();
+    let tat = tat.unwrap_or(now).max(now);
+    let new_tat = tat + interval;
+    let allow_at = new_tat.saturating_sub(quota.period);
+
+    if now < allow_at {
+        let decision = Decision {
+            allowed: false,
+            limit: quota.limit,
+            remaining: 0,
+            reset: tat - now,
+            retry_after: allow_at - now,
+        };
+        return (decision, None);
+    }
+
+    let remaining = (now + quota.period - new_tat) / interval;
+    let decision = Decision {
+        allowed: true,
+        limit: quota.limit,
+        remaining: remaining.min(quota.limit as u64) as u32,
+        reset: new_tat - now,
+        retry_after: 0,
+    };
+    (decision, Some(new_tat))
+}
+
+/// 限流存储
+#[async_trait]
+pub trait RateLimitStore: Send + Sync + 'static {
+    /// 检查多个配额 全部允许时才各消耗一次 `now` 单位 ms
+    ///
+    /// 返回与 `checks` 一一对应的结果 任一被拒绝时不消耗任何配额
+    async fn check(&self, checks: &[(String, Quota)], now: u64) -> Result<Vec<Decision>, Res<()>>;
+}
+
+/// 内存限流存储 适用于单实例部署
+#[derive(Default)]
+pub struct MemoryRateLimitStore {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    tats: HashMap<String, u64>,
+    purged_at: u64,
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn check(&self, checks: &[(String, Quota)], now: u64) -> Result<Vec<Decision>, Res<()>> {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        // 每秒最多清除一次已恢复满额的记录
+        if now >= state.purged_at + 1000 {
+            state.tats.retain(|_, tat| *tat > now);
+            state.purged_at = now;
+        }
+
+        let results: Vec<_> = checks
+            .iter()
+            .map(|(key, quota)| gcra(quota, state.tats.get(key).copied(), now))
+            .collect();
+        // 被拒绝的请求不消耗其他规则的配额
+        if results.iter().all(|(decision, _)| decision.allowed) {
+            for ((key, _), (_, tat)) in checks.iter().zip(&results) {
+                if let Some(tat) = tat {
+                    state.tats.insert(key.clone(), *tat);
+                }
+            }
+        }
+        Ok(results.into_iter().map(|(decision, _)| decision).collect())
+    }
+}
+
+#[test]
+fn gcra_quota() {
+    let quota = Quota::per_second(2);
+    let (first, tat) = gcra(&quota, None, 0);
+    assert!(first.allowed);
+    assert_eq!(first.remaining, 1);
+    let (second, tat) = gcra(&quota, tat, 0);
+    assert!(second.allowed);
+    assert_eq!(second.remaining, 0);
+
+    let (third, _) = gcra(&quota, tat, 100);
+    assert!(!third.allowed);
+    assert_eq!(third.retry_after, 400);
+
+    // 每 500ms 恢复一次
+    let (fourth, tat) = gcra(&quota, tat, 500);
+    assert!(fourth.allowed);
+    let (fifth, _) = gcra(&quota, tat, 2000);
+    assert_eq!(fifth.remaining, 1);
+}
+
+#[test]
+fn memory_rejects_without_charging() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    runtime.block_on(async {
+        let store = MemoryRateLimitStore::new();
+        let global = ("global".to_string(), Quota::per_minute(10));
+        let login = ("login".to_string(), Quota::per_minute(1));
+        let checks = [global.clone(), login];
+
+        assert!(store
+            .check(&checks, 0)
+            .await
+            .unwrap()
+            .iter()
+            .all(|d| d.allowed));
+        // login 超限时 global 不被消耗
+        for _ in 0..5 {
+            let decisions = store.check(&checks, 0).await.unwrap();
+            assert!(decisions[0].allowed && !decisions[1].allowed);
+        }
+        let decisions = store.check(&[global], 0).await.unwrap();
+        assert_eq!(decisions[0].remaining, 8);
+    });
+}
@@ -0,0 +1,415 @@
+use std::{
+    borrow::Cow,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use axum::{
+    async_trait,
+    body::Body,
+    extract::FromRequestParts,
+    headers::{Cookie, HeaderMapExt},
+    http::{header::SET_COOKIE, request::Parts, HeaderMap, HeaderValue, Request},
+    response::{IntoResponse, Response},
+};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use futures_util::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::Sha256;
+use tower::{Layer, Service};
+use uuid::Uuid;
+
+use crate::{res::Res, utils::timestamp};
+
+mod store;
+
+pub use store::*;
+
+/// 服务端会话中间件
+///
+/// cookie 中只保存签名后的会话 id 会话数据保存在服务端 可随时销毁
+///
+/// # Examples
+/// ```ignore
+/// let store = PgSessionStore::new(pool);
+/// store.migrate().await?;
+///
+/// let app = Router::new()
+///     .route("/login", post(login))
+///     .route("/logout", post(logout))
+///     .layer(SessionLayer::new(store, env::var("SESSION_SECRET")?).secure(true));
+///
+/// async fn login(session: Session, VJsonOrForm(user): VJsonOrForm<User>) -> utils::Result<()> {
+///     // 验证用户
+///     session.regenerate();
+///     session.insert("user", &user)?;
+///     Ok(Res::ok(()))
+/// }
+///
+/// async fn logout(session: Session) -> utils::Result<()> {
+///     session.destroy();
+///     Ok(Res::ok(()))
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SessionLayer {
+    store: Arc<dyn SessionStore>,
+    config: Arc<SessionConfig>,
+}
+
+struct SessionConfig {
+    name: Cow<'static, str>,
+    secret: Vec<u8>,
+    idle_timeout: u64,
+    absolute_timeout: u64,
+    path: Cow<'static, str>,
+    secure: bool,
+}
+
+impl SessionLayer {
+    /// `secret` 用于签名会话 id
+    pub fn new<R: SessionStore, K: AsRef<[u8]>>(store: R, secret: K) -> Self {
+        let config = SessionConfig {
+            name: Cow::Borrowed("sid"),
+            secret: secret.as_ref().to_vec(),
+            idle_timeout: 60 * 30,
+            absolute_timeout: 60 * 60 * 12,
+            path: Cow::Borrowed("/"),
+            secure: false,
+        };
+        Self {
+            store: Arc::new(store),
+            config: Arc::new(config),
+        }
+    }
+
+    /// cookie 名称 默认 `sid`
+    pub fn cookie_name<N: Into<Cow<'static, str>>>(mut self, name: N) -> Self {
+        self.config_mut().name = name.into();
+        self
+    }
+
+    /// 空闲超时 超过该时间未访问会话失效 默认30分钟 单位 s
+    pub fn idle_timeout(mut self, timeout: u64) -> Self {
+        self.config_mut().idle_timeout = timeout;
+        self
+    }
+
+    /// 绝对超时 会话创建后超过该时间失效 默认12小时 单位 s
+    pub fn absolute_timeout(mut self, timeout: u64) -> Self {
+        self.config_mut().absolute_timeout = timeout;
+        self
+    }
+
+    /// cookie 的 Path 默认 `/`
+    pub fn path<P: Into<Cow<'static, str>>>(mut self, path: P) -> Self {
+        self.config_mut().path = path.into();
+        self
+    }
+
+    /// cookie 是否仅通过 https 发送
+    pub fn secure(mut self, secure: bool) -> Self {
+        self.config_mut().secure = secure;
+        self
+    }
+
+    fn config_mut(&mut self) -> &mut SessionConfig {
+        Arc::get_mut(&mut self.config).expect("SessionLayer 配置需在 layer 前完成")
+    }
+}
+
+impl<S> Layer<S> for SessionLayer {
+    type Service = SessionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SessionService {
+            inner,
+            store: self.store.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SessionService<S> {
+    inner: S,
+    store: Arc<dyn SessionStore>,
+    config: Arc<SessionConfig>,
+}
+
+impl<S> Service<Request<Body>> for SessionService<S>
+where
+    S: Service<Request<Body>, Response = Response> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let store = self.store.clone();
+        let config = self.config.clone();
+        let id = config.session_id(req.headers());
+
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let now = timestamp();
+            let record = match &id {
+                Some(id) => match store.load(id).await {
+                    Ok(record) => record.filter(|r| !config.expired(r, now)),
+                    Err(err_res) => return Ok(err_res.into_response()),
+                },
+                None => None,
+            };
+
+            let state = match record {
+                Some(record) => State::new(id.clone(), record),
+                None => State::new(None, SessionRecord::default()),
+            };
+            let session = Session(Arc::new(Mutex::new(state)));
+            req.extensions_mut().insert(session.clone());
+
+            let mut res = inner.call(req).await?;
+            if let Err(err_res) = commit(&store, &config, &session, id, &mut res).await {
+                return Ok(err_res.into_response());
+            }
+            Ok(res)
+        })
+    }
+}
+
+/// 保存会话 并在会话 id 变化时写入 cookie
+///
+/// `cookie_id` 为请求 cookie 中携带的会话 id
+async fn commit(
+    store: &Arc<dyn SessionStore>,
+    config: &SessionConfig,
+    session: &Session,
+    cookie_id: Option<String>,
+    res: &mut Response,
+) -> Result<(), Res<()>> {
+    let state = session.lock().clone();
+    let now = timestamp();
+
+    if state.destroyed {
+        if let Some(id) = &state.id {
+            store.delete(id).await?;
+        }
+        if cookie_id.is_some() {
+            config.set_cookie(res, "", 0);
+        }
+        return Ok(());
+    }
+
+    // 没有数据的新会话不保存
+    if state.id.is_none() && !state.changed {
+        return Ok(());
+    }
+
+    let mut record = state.record;
+    let id = match state.id {
+        // 已有会话只更新变化的部分 会话已被其他请求销毁时不会重新创建
+        Some(id) if !state.regenerate => {
+            record.accessed_at = now;
+            record.expires_at = config.expires_at(&record, now);
+            if state.changed {
+                store.update(&id, &record).await?;
+            } else {
+                store
+                    .touch(&id, record.accessed_at, record.expires_at)
+                    .await?;
+            }
+            id
+        }
+        // 重新生成 id 时保留创建时间 绝对超时不随登录重新计算
+        old => {
+            match old {
+                Some(old) => store.delete(&old).await?,
+                None => record.created_at = now,
+            }
+            record.accessed_at = now;
+            record.expires_at = config.expires_at(&record, now);
+            let id = state.pending.unwrap_or_else(new_id);
+            store.save(&id, &record).await?;
+            id
+        }
+    };
+
+    if cookie_id.as_deref() != Some(id.as_str()) {
+        let value = format!("{id}.{}", config.sign(&id));
+        config.set_cookie(res, &value, config.absolute_timeout);
+    }
+    Ok(())
+}
+
+impl SessionConfig {
+    /// 读取并验证 cookie 中的会话 id
+    fn session_id(&self, headers: &HeaderMap) -> Option<String> {
+        let cookie = headers.typed_get::<Cookie>()?;
+        let (id, signature) = cookie.get(&self.name)?.rsplit_once('.')?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        self.mac(id).verify_slice(&signature).ok()?;
+        Some(id.to_string())
+    }
+
+    fn sign(&self, id: &str) -> String {
+        URL_SAFE_NO_PAD.encode(self.mac(id).finalize().into_bytes())
+    }
+
+    fn mac(&self, id: &str) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.secret).expect("HMAC 可接受任意长度密钥");
+        mac.update(id.as_bytes());
+        mac
+    }
+
+    /// 本次访问后的过期时间
+    fn expires_at(&self, record: &SessionRecord, now: u64) -> u64 {
+        (now + self.idle_timeout).min(record.created_at + self.absolute_timeout)
+    }
+
+    /// 是否已超过空闲超时或绝对超时
+    fn expired(&self, record: &SessionRecord, now: u64) -> bool {
+        now >= record.accessed_at + self.idle_timeout
+            || now >= record.created_at + self.absolute_timeout
+    }
+
+    fn set_cookie(&self, res: &mut Response, value: &str, max_age: u64) {
+        let secure = if self.secure { "; Secure" } else { "" };
+        let cookie = format!(
+            "{}={value}; Path={}; Max-Age={max_age}; HttpOnly; SameSite=Lax{secure}",
+            self.name, self.path
+        );
+        if let Ok(cookie) = HeaderValue::try_from(cookie) {
+            res.headers_mut().append(SET_COOKIE, cookie);
+        }
+    }
+}
+
+fn new_id() -> String {
+    Uuid::new_v4().simple().to_string()
+}
+
+#[derive(Clone)]
+struct State {
+    id: Option<String>,
+    record: SessionRecord,
+    changed: bool,
+    regenerate: bool,
+    destroyed: bool,
+    /// 提前生成的新会话 id 保存时使用
+    pending: Option<String>,
+}
+
+impl State {
+    fn new(id: Option<String>, record: SessionRecord) -> Self {
+        Self {
+            id,
+            record,
+            changed: false,
+            regenerate: false,
+            destroyed: false,
+            pending: None,
+        }
+    }
+}
+
+/// 当前请求的会话 需配合 `SessionLayer` 使用
+///
+/// 修改在响应返回时保存
+#[derive(Clone)]
+pub struct Session(Arc<Mutex<State>>);
+
+impl Session {
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// 会话 id 新会话保存前为 None
+    pub fn id(&self) -> Option<String> {
+        self.lock().id.clone()
+    }
+
+    /// 响应返回后 cookie 中的会话 id 没有会话或会话已销毁时为 None
+    ///
+    /// 需要新 id 时提前生成 供 `CsrfLayer` 在 handler 之后按新会话签发 token
+    pub(crate) fn response_id(&self) -> Option<String> {
+        let mut state = self.lock();
+        if state.destroyed || (state.id.is_none() && !state.changed) {
+            return None;
+        }
+        match &state.id {
+            Some(id) if !state.regenerate => Some(id.clone()),
+            _ => Some(state.pending.get_or_insert_with(new_id).clone()),
+        }
+    }
+
+    /// 读取数据
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let value = self.lock().record.data.get(key).cloned()?;
+        serde_json::from_value(value).ok()
+    }
+
+    /// 写入数据
+    pub fn insert<T: Serialize>(&self, key: &str, value: T) -> Result<(), Res<()>> {
+        let value = serde_json::to_value(value).map_err(Res::internal_error)?;
+        let mut state = self.lock();
+        // 销毁后再写入数据时使用新的会话 id
+        if state.destroyed {
+            state.destroyed = false;
+            state.regenerate = true;
+        }
+        state.record.data.insert(key.to_string(), value);
+        state.changed = true;
+        Ok(())
+    }
+
+    /// 删除数据
+    pub fn remove(&self, key: &str) {
+        let mut state = self.lock();
+        state.changed |= state.record.data.remove(key).is_some();
+    }
+
+    /// 清空数据 保留会话
+    pub fn clear(&self) {
+        let mut state = self.lock();
+        state.record.data.clear();
+        state.changed = true;
+    }
+
+    /// 更换会话 id 并保留数据 登录成功后调用以防止会话固定攻击
+    pub fn regenerate(&self) {
+        let mut state = self.lock();
+        state.regenerate = true;
+        state.changed = true;
+    }
+
+    /// 销毁会话 退出登录时调用
+    pub fn destroy(&self) {
+        let mut state = self.lock();
+        state.record.data.clear();
+        state.destroyed = true;
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Session
+where
+    S: Send + Sync,
+{
+    type Rejection = Res<()>;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Session>()
+            .cloned()
+            .ok_or_else(|| Res::internal_error("未配置 SessionLayer"))
+    }
+}
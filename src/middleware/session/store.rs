@@ -0,0 +1,261 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use axum::async_trait;
+use bb8::Pool;
+use diesel::{
+    sql_query,
+    sql_types::{BigInt, Text},
+    QueryableByName,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    database::postgres::{db_error, PgPool},
+    res::Res,
+    utils::timestamp,
+};
+
+/// 会话记录
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    /// 会话数据
+    pub data: HashMap<String, Value>,
+    /// 创建时间
+    pub created_at: u64,
+    /// 最后访问时间
+    pub accessed_at: u64,
+    /// 过期时间 到期后可清除
+    pub expires_at: u64,
+}
+
+/// 会话存储
+#[async_trait]
+pub trait SessionStore: Send + Sync + 'static {
+    /// 读取未过期的会话
+    async fn load(&self, id: &str) -> Result<Option<SessionRecord>, Res<()>>;
+
+    /// 保存新会话
+    async fn save(&self, id: &str, record: &SessionRecord) -> Result<(), Res<()>>;
+
+    /// 更新已有会话的数据与过期时间 会话已被删除时不做任何操作
+    async fn update(&self, id: &str, record: &SessionRecord) -> Result<(), Res<()>>;
+
+    /// 只更新已有会话的访问时间与过期时间 会话已被删除时不做任何操作
+    async fn touch(&self, id: &str, accessed_at: u64, expires_at: u64) -> Result<(), Res<()>>;
+
+    /// 删除会话
+    async fn delete(&self, id: &str) -> Result<(), Res<()>>;
+}
+
+/// 内存会话存储 适用于单实例部署
+#[derive(Default)]
+pub struct MemorySessionStore {
+    sessions: Mutex<HashMap<String, SessionRecord>>,
+    /// 上次清除过期会话的时间
+    swept_at: AtomicU64,
+}
+
+/// 清除过期会话的最短间隔 单位 s
+const SWEEP_INTERVAL: u64 = 60;
+
+impl MemorySessionStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SessionStore for MemorySessionStore {
+    async fn load(&self, id: &str) -> Result<Option<SessionRecord>, Res<()>> {
+        let sessions = self.sessions.lock().unwrap_or_else(|err| err.into_inner());
+        let record = sessions.get(id).filter(|r| r.expires_at > timestamp());
+        Ok(record.cloned())
+    }
+
+    async fn save(&self, id: &str, record: &SessionRecord) -> Result<(), Res<()>> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|err| err.into_inner());
+        // 创建会话时按间隔清除过期会话
+        let now = timestamp();
+        if now >= self.swept_at.load(Ordering::Relaxed) + SWEEP_INTERVAL {
+            self.swept_at.store(now, Ordering::Relaxed);
+            sessions.retain(|_, record| record.expires_at > now);
+        }
+        sessions.insert(id.to_string(), record.clone());
+        Ok(())
+    }
+
+    async fn update(&self, id: &str, record: &SessionRecord) -> Result<(), Res<()>> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(session) = sessions.get_mut(id) {
+            *session = record.clone();
+        }
+        Ok(())
+    }
+
+    async fn touch(&self, id: &str, accessed_at: u64, expires_at: u64) -> Result<(), Res<()>> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(session) = sessions.get_mut(id) {
+            session.accessed_at = accessed_at;
+            session.expires_at = expires_at;
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), Res<()>> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(|err| err.into_inner());
+        sessions.remove(id);
+        Ok(())
+    }
+}
+
+/// Postgres 会话存储 多实例共享
+///
+/// 使用前需调用 `migrate` 创建 `sessions` 表
+pub struct PgSessionStore {
+    pool: Pool<PgPool>,
+}
+
+#[derive(QueryableByName)]
+struct Row {
+    #[diesel(sql_type = Text)]
+    data: String,
+    #[diesel(sql_type = BigInt)]
+    created_at: i64,
+    #[diesel(sql_type = BigInt)]
+    accessed_at: i64,
+    #[diesel(sql_type = BigInt)]
+    expires_at: i64,
+}
+
+impl PgSessionStore {
+    pub fn new(pool: Pool<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// 创建 `sessions` 表
+    pub async fn migrate(&self) -> Result<(), Res<()>> {
+        // 仅在数据库操作中引入 其 `load` 会遮蔽原子类型的同名方法
+        use diesel_async::RunQueryDsl;
+
+        let mut conn = self.pool.get().await.map_err(db_error)?;
+        sql_query(
+            "CREATE TABLE IF NOT EXISTS sessions (
+                id TEXT PRIMARY KEY,
+                data TEXT NOT NULL,
+                created_at BIGINT NOT NULL,
+                accessed_at BIGINT NOT NULL,
+                expires_at BIGINT NOT NULL
+            )",
+        )
+        .execute(&mut conn)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SessionStore for PgSessionStore {
+    async fn load(&self, id: &str) -> Result<Option<SessionRecord>, Res<()>> {
+        use diesel_async::RunQueryDsl;
+
+        let mut conn = self.pool.get().await.map_err(db_error)?;
+        let rows = sql_query(
+            "SELECT data, created_at, accessed_at, expires_at FROM sessions
+            WHERE id = $1 AND expires_at > $2",
+        )
+        .bind::<Text, _>(id)
+        .bind::<BigInt, _>(timestamp() as i64)
+        .load::<Row>(&mut conn)
+        .await
+        .map_err(db_error)?;
+
+        let Some(row) = rows.into_iter().next() else {
+            return Ok(None);
+        };
+        Ok(Some(SessionRecord {
+            data: serde_json::from_str(&row.data).map_err(db_error)?,
+            created_at: row.created_at as u64,
+            accessed_at: row.accessed_at as u64,
+            expires_at: row.expires_at as u64,
+        }))
+    }
+
+    async fn save(&self, id: &str, record: &SessionRecord) -> Result<(), Res<()>> {
+        use diesel_async::RunQueryDsl;
+
+        let mut conn = self.pool.get().await.map_err(db_error)?;
+        // 新会话时顺带清除过期会话
+        sql_query("DELETE FROM sessions WHERE expires_at <= $1")
+            .bind::<BigInt, _>(timestamp() as i64)
+            .execute(&mut conn)
+            .await
+            .map_err(db_error)?;
+
+        let data = serde_json::to_string(&record.data).map_err(db_error)?;
+        sql_query(
+            "INSERT INTO sessions (id, data, created_at, accessed_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (id) DO UPDATE SET data = EXCLUDED.data,
+                accessed_at = EXCLUDED.accessed_at, expires_at = EXCLUDED.expires_at",
+        )
+        .bind::<Text, _>(id)
+        .bind::<Text, _>(data)
+        .bind::<BigInt, _>(record.created_at as i64)
+        .bind::<BigInt, _>(record.accessed_at as i64)
+        .bind::<BigInt, _>(record.expires_at as i64)
+        .execute(&mut conn)
+        .await
+        .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn update(&self, id: &str, record: &SessionRecord) -> Result<(), Res<()>> {
+        use diesel_async::RunQueryDsl;
+
+        let mut conn = self.pool.get().await.map_err(db_error)?;
+        let data = serde_json::to_string(&record.data).map_err(db_error)?;
+        sql_query("UPDATE sessions SET data = $2, accessed_at = $3, expires_at = $4 WHERE id = $1")
+            .bind::<Text, _>(id)
+            .bind::<Text, _>(data)
+            .bind::<BigInt, _>(record.accessed_at as i64)
+            .bind::<BigInt, _>(record.expires_at as i64)
+            .execute(&mut conn)
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn touch(&self, id: &str, accessed_at: u64, expires_at: u64) -> Result<(), Res<()>> {
+        use diesel_async::RunQueryDsl;
+
+        let mut conn = self.pool.get().await.map_err(db_error)?;
+        sql_query("UPDATE sessions SET accessed_at = $2, expires_at = $3 WHERE id = $1")
+            .bind::<Text, _>(id)
+            .bind::<BigInt, _>(accessed_at as i64)
+            .bind::<BigInt, _>(expires_at as i64)
+            .execute(&mut conn)
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+
+    async fn delete(&self, id: &str) -> Result<(), Res<()>> {
+        use diesel_async::RunQueryDsl;
+
+        let mut conn = self.pool.get().await.map_err(db_error)?;
+        sql_query("DELETE FROM sessions WHERE id = $1")
+            .bind::<Text, _>(id)
+            .execute(&mut conn)
+            .await
+            .map_err(db_error)?;
+        Ok(())
+    }
+}
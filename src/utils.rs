@@ -3,6 +3,7 @@ use std::{
     fs::{self, File},
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::Path,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use crate::res::Res;
@@ -53,3 +54,19 @@ pub fn create_log_file(path: String) -> File {
         .open(path)
         .expect("日志文件创建失败")
 }
+
+/// 当前时间戳 单位 s
+pub fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// 当前时间戳 单位 ms
+pub fn timestamp_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}